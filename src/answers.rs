@@ -0,0 +1,167 @@
+use advent_code_2021::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+pub const ANSWERS_FILE: &str = "./inputs/answers.toml";
+
+const HEADER: &str = "\
+# Confirmed answers for the puzzle inputs in this directory, keyed by day.
+# Checked by `run`, `run --day <day> --part <part> --record` adds a part that isn't listed yet.
+";
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+struct DayAnswers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part_one: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part_two: Option<String>,
+}
+
+impl DayAnswers {
+    fn part(&self, part: u8) -> Option<&String> {
+        match part {
+            1 => self.part_one.as_ref(),
+            _ => self.part_two.as_ref(),
+        }
+    }
+
+    fn part_mut(&mut self, part: u8) -> &mut Option<String> {
+        match part {
+            1 => &mut self.part_one,
+            _ => &mut self.part_two,
+        }
+    }
+}
+
+/// Known answers of the real puzzle inputs
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Answers(BTreeMap<String, DayAnswers>);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Unknown,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected } if expected.contains('\n') => {
+                write!(f, "FAIL, expected:\n{}", expected)
+            }
+            Verdict::Fail { expected } => write!(f, "FAIL, expected {}", expected),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+fn day_key(day: u8) -> String {
+    format!("day_{:02}", day)
+}
+
+impl Answers {
+    /// Load the answers file, a missing file means no answers are known yet
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(|e| Error::parse(e).with_path(path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::from(e).with_path(path)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self).expect("Answers serialize to toml");
+        std::fs::write(path, format!("{}\n{}", HEADER, content))
+            .map_err(|e| Error::from(e).with_path(path))
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.0
+            .get(&day_key(day))
+            .and_then(|answers| answers.part(part))
+            .map(String::as_str)
+    }
+
+    pub fn check(&self, day: u8, part: u8, answer: &str) -> Verdict {
+        match self.get(day, part) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_string(),
+            },
+        }
+    }
+
+    /// Store `answer` if the part has none yet, returns whether it was recorded
+    pub fn record(&mut self, day: u8, part: u8, answer: &str) -> bool {
+        let known = self.0.entry(day_key(day)).or_default().part_mut(part);
+        if known.is_none() {
+            known.replace(answer.to_string());
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::answers::{Answers, Verdict, ANSWERS_FILE};
+    use advent_code_2021::solution::solutions;
+    use advent_code_2021::utils::io::Input;
+    use std::path::Path;
+
+    #[test]
+    fn check_and_record() {
+        let mut answers: Answers = toml::from_str("[day_01]\npart_one = \"7\"\n").unwrap();
+        assert_eq!(answers.check(1, 1, "7"), Verdict::Pass);
+        assert_eq!(
+            answers.check(1, 1, "8"),
+            Verdict::Fail {
+                expected: "7".to_string()
+            }
+        );
+        assert_eq!(answers.check(1, 2, "5"), Verdict::Unknown);
+
+        assert!(answers.record(1, 2, "5"));
+        assert!(!answers.record(1, 1, "8"));
+        assert_eq!(answers.check(1, 1, "7"), Verdict::Pass);
+        assert_eq!(answers.check(1, 2, "5"), Verdict::Pass);
+
+        let saved = toml::to_string(&answers).unwrap();
+        assert_eq!(toml::from_str::<Answers>(&saved).unwrap(), answers);
+    }
+
+    #[test]
+    fn recorded_answers() -> advent_code_2021::error::Result<()> {
+        let answers = Answers::load(Path::new(ANSWERS_FILE))?;
+        let mut failures = Vec::new();
+        for (day, solution) in solutions() {
+            let (part_one, part_two) = (answers.get(day, 1), answers.get(day, 2));
+            if part_one.is_none() && part_two.is_none() {
+                continue;
+            }
+            let input = Input::day(day);
+            let parsed = input.parse_with(|reader| solution.run_parse(reader))?;
+            if let Some(expected) = part_one {
+                let result = solution.run_part_one(parsed.as_ref());
+                if result != expected {
+                    failures.push(format!("day {} part 1: {}", day, result));
+                }
+            }
+            if let Some(expected) = part_two {
+                let result = solution.run_part_two(parsed.as_ref());
+                if result != expected {
+                    failures.push(format!("day {} part 2: {}", day, result));
+                }
+            }
+        }
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+        Ok(())
+    }
+}
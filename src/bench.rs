@@ -0,0 +1,148 @@
+use advent_code_2021::error::Result;
+use advent_code_2021::solution::DynSolution;
+use advent_code_2021::utils::io::Input;
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+fn nanos<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_nanos().try_into().unwrap_or(u64::MAX))
+}
+
+/// Spread of the durations of repeated runs, serialized as nanoseconds
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct Timings {
+    #[serde(rename = "min_ns", serialize_with = "nanos")]
+    pub min: Duration,
+    #[serde(rename = "median_ns", serialize_with = "nanos")]
+    pub median: Duration,
+    #[serde(rename = "max_ns", serialize_with = "nanos")]
+    pub max: Duration,
+}
+
+impl Timings {
+    /// Summarize at least one sample
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "Timings need at least one sample");
+        samples.sort_unstable();
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Self {
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        }
+    }
+
+    /// Time `runs` calls of `f`, the result of each call is dropped outside of the measure
+    pub fn measure<T>(runs: usize, mut f: impl FnMut() -> T) -> Self {
+        let samples = (0..runs.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                let result = f();
+                let elapsed = start.elapsed();
+                drop(result);
+                elapsed
+            })
+            .collect();
+        Self::from_samples(samples)
+    }
+}
+
+/// Timings of a day, parsing and each part are measured separately
+#[derive(Debug, Clone, Serialize)]
+pub struct DayBench {
+    pub day: u8,
+    pub runs: usize,
+    pub parse: Timings,
+    pub part_one: Timings,
+    pub part_two: Timings,
+}
+
+/// Run `day` `runs` times on `input`.
+/// The input is read once up front so only parsing is measured, not the disk.
+pub fn bench_day(
+    day: u8,
+    solution: &dyn DynSolution,
+    input: &Input,
+    runs: usize,
+) -> Result<DayBench> {
+    let content = input.parse_with(|reader| {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        Ok(content)
+    })?;
+    // Parse once outside of the measures to surface errors
+    let parsed = solution
+        .run_parse(&mut content.as_slice())
+        .map_err(|e| match input.path() {
+            Some(path) => e.with_path(path),
+            None => e,
+        })?;
+
+    Ok(DayBench {
+        day,
+        runs: runs.max(1),
+        parse: Timings::measure(runs, || solution.run_parse(&mut content.as_slice())),
+        part_one: Timings::measure(runs, || solution.run_part_one(parsed.as_ref())),
+        part_two: Timings::measure(runs, || solution.run_part_two(parsed.as_ref())),
+    })
+}
+
+impl Display for DayBench {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (step, timings) in [
+            ("parse", &self.parse),
+            ("part 1", &self.part_one),
+            ("part 2", &self.part_two),
+        ] {
+            writeln!(
+                f,
+                "Day {:>2} {:<6}  min {:>10}  median {:>10}  max {:>10}",
+                self.day,
+                step,
+                format!("{:.1?}", timings.min),
+                format!("{:.1?}", timings.median),
+                format!("{:.1?}", timings.max),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bench::{bench_day, Timings};
+    use advent_code_2021::solution::solution;
+    use advent_code_2021::utils::io::Input;
+    use std::time::Duration;
+
+    #[test]
+    fn timings_summary() {
+        let ms = Duration::from_millis;
+        let odd = Timings::from_samples(vec![ms(5), ms(1), ms(3)]);
+        assert_eq!((odd.min, odd.median, odd.max), (ms(1), ms(3), ms(5)));
+        let even = Timings::from_samples(vec![ms(4), ms(1), ms(2), ms(8)]);
+        assert_eq!((even.min, even.median, even.max), (ms(1), ms(3), ms(8)));
+    }
+
+    #[test]
+    fn bench_example() -> advent_code_2021::error::Result<()> {
+        let input = Input::inline("199\n200\n208\n210\n200\n207\n240\n269\n260\n263");
+        let bench = bench_day(1, solution(1).unwrap(), &input, 3)?;
+        assert_eq!(bench.runs, 3);
+        assert!(bench.parse.min <= bench.parse.median && bench.parse.median <= bench.parse.max);
+
+        let json = serde_json::to_value(&bench).unwrap();
+        assert_eq!(json["day"], 1);
+        assert!(json["part_two"]["median_ns"].is_u64());
+        Ok(())
+    }
+}
@@ -32,19 +32,21 @@
 //
 // How many measurements are larger than the previous measurement?
 
-fn count_increased_measures<'a>(
+use crate::error;
+use crate::solution::Solution;
+use crate::utils::io;
+use std::io::BufRead;
+
+pub fn count_increased_measures<'a>(
     measures: impl Iterator<Item = &'a usize> + Clone,
     skip: usize,
 ) -> usize {
     let iter1 = measures.clone();
     let iter2 = measures.skip(skip);
-    iter1
-        .zip(iter2)
-        .map(|(a, b)| a.lt(b).then_some(1usize).unwrap_or(0))
-        .sum()
+    iter1.zip(iter2).filter(|(a, b)| a.lt(b)).count()
 }
 
-fn count_increased_measure_sliding_windows(measures: &[usize], windows_size: usize) -> usize {
+pub fn count_increased_measure_sliding_windows(measures: &[usize], windows_size: usize) -> usize {
     count_increased_measures(
         measures
             .windows(windows_size)
@@ -55,6 +57,26 @@ fn count_increased_measure_sliding_windows(measures: &[usize], windows_size: usi
     )
 }
 
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_vec(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        count_increased_measures(input.iter(), 1)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        count_increased_measure_sliding_windows(input, 3)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::day_01::{count_increased_measure_sliding_windows, count_increased_measures};
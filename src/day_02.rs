@@ -1,35 +1,47 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
+use std::io::BufRead;
 use std::str::FromStr;
 
-enum Command {
+pub enum Command {
     Forward(i32),
     Down(i32),
     Up(i32),
 }
 
 impl FromStr for Command {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (command, value): (String, i32) = sscanf::scanf!(s, "{} {}", String, i32).ok_or(())?;
+        let (command, value): (String, i32) = sscanf::scanf!(s, "{} {}", String, i32)
+            .ok_or_else(|| ParseError::new("`<command> <units>`", s))?;
         Ok(match command.to_lowercase().as_str() {
             "forward" => Self::Forward(value),
             "down" => Self::Down(value),
             "up" => Self::Up(-value),
-            _ => return Err(()),
+            _ => return Err(ParseError::new("forward, down or up", command)),
         })
     }
 }
 
-trait Day2Solver {
+pub trait Day2Solver {
     fn compute_command(&mut self, command: &Command);
     fn compute_solution(&self) -> i32;
 }
 
-struct Position {
+pub struct Position {
     horizontal: i32,
     depth: i32,
 }
 
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Position {
     pub fn new() -> Self {
         Self {
@@ -56,11 +68,17 @@ impl Day2Solver for Position {
     }
 }
 
-struct Aimed {
+pub struct Aimed {
     position: Position,
     aim: i32,
 }
 
+impl Default for Aimed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Aimed {
     pub fn new() -> Self {
         Self {
@@ -88,7 +106,7 @@ impl Day2Solver for Aimed {
     }
 }
 
-fn solve_position_with_solver<Solver: Day2Solver>(solver: Solver, commands: &[Command]) -> i32 {
+pub fn solve_position_with_solver<Solver: Day2Solver>(solver: Solver, commands: &[Command]) -> i32 {
     let position: Box<dyn Day2Solver> =
         Box::new(commands.iter().fold(solver, |mut position, command| {
             position.compute_command(command);
@@ -97,6 +115,26 @@ fn solve_position_with_solver<Solver: Day2Solver>(solver: Solver, commands: &[Co
     position.compute_solution()
 }
 
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<Command>;
+    type PartOne = i32;
+    type PartTwo = i32;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_vec(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_position_with_solver(Position::new(), input)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_position_with_solver(Aimed::new(), input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::day_02::*;
@@ -1,44 +1,65 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
 use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
-struct Entry<const SIZE: usize>([u8; SIZE]);
+/// Bits of a diagnostic report line, the most significant first
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Entry(Vec<u8>);
 
-impl<const SIZE: usize> FromStr for Entry<SIZE> {
-    type Err = ();
+/// Widest entry whose number fits in a `u32`
+const MAX_WIDTH: usize = 32;
+
+impl FromStr for Entry {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let bits = s
             .chars()
-            .map(|c| c.eq(&'1').then_some(1u8).unwrap_or(0))
-            .collect::<Vec<u8>>();
-        assert_eq!(bits.len(), SIZE);
-        Ok(Self::try_from(bits).unwrap())
+            .enumerate()
+            .map(|(column, c)| match c {
+                '0' => Ok(0u8),
+                '1' => Ok(1u8),
+                c => Err(ParseError::new("a bit", c).at_column(column + 1)),
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        if bits.len() > MAX_WIDTH {
+            return Err(ParseError::new(format!("at most {} bits", MAX_WIDTH), s));
+        }
+        Ok(Self(bits))
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|bit| write!(f, "{}", bit))
     }
 }
 
-impl<const SIZE: usize> Entry<SIZE> {
+impl Entry {
+    fn width(&self) -> usize {
+        self.0.len()
+    }
+
     fn to_number(&self) -> u32 {
         self.0
             .iter()
-            .zip((0..SIZE as u32).rev())
-            .map(|(bit, exp)| *bit as u32 * 2u32.pow(exp))
-            .sum()
+            .fold(0, |number, &bit| (number << 1) | bit as u32)
     }
 
     fn reverse(&self) -> Self {
         Self(
-            <[u8; SIZE]>::try_from(
-                self.0
-                    .iter()
-                    .copied()
-                    .map(|b| match b {
-                        1 => 0,
-                        _ => 1,
-                    })
-                    .collect::<Vec<u8>>(),
-            )
-            .unwrap(),
+            self.0
+                .iter()
+                .map(|b| match b {
+                    1 => 0,
+                    _ => 1,
+                })
+                .collect(),
         )
     }
 
@@ -47,20 +68,12 @@ impl<const SIZE: usize> Entry<SIZE> {
     }
 }
 
-impl<const SIZE: usize> TryFrom<Vec<u8>> for Entry<SIZE> {
-    type Error = <[u8; SIZE] as TryFrom<Vec<u8>>>::Error;
-
-    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        Ok(Self(<[u8; SIZE]>::try_from(value)?))
-    }
+/// Width shared by all the entries, the one of the first entry
+fn width(entries: &[Entry]) -> usize {
+    entries.first().map(Entry::width).unwrap_or_default()
 }
 
-fn find_common_in_column<const SIZE: usize>(
-    entries: &[Entry<SIZE>],
-    column: usize,
-    default: u8,
-    common: bool,
-) -> u8 {
+fn find_common_in_column(entries: &[Entry], column: usize, default: u8, common: bool) -> u8 {
     let (zeros, ones) =
         entries
             .iter()
@@ -71,33 +84,33 @@ fn find_common_in_column<const SIZE: usize>(
                 _ => unreachable!(),
             });
     match zeros.cmp(&ones) {
-        Ordering::Less => common.then_some(1).unwrap_or(0),
+        Ordering::Less => u8::from(common),
         Ordering::Equal => default,
-        Ordering::Greater => common.then_some(0).unwrap_or(1),
+        Ordering::Greater => u8::from(!common),
     }
 }
 
-fn find_common_uncommon<const SIZE: usize>(entries: &[Entry<SIZE>]) -> (Entry<SIZE>, Entry<SIZE>) {
-    let common: Vec<u8> = (0..SIZE)
-        .map(|i| find_common_in_column(entries, i, 1, true))
-        .collect();
-    let common: Entry<SIZE> = Entry::try_from(common).unwrap();
+fn find_common_uncommon(entries: &[Entry]) -> (Entry, Entry) {
+    let common = Entry(
+        (0..width(entries))
+            .map(|i| find_common_in_column(entries, i, 1, true))
+            .collect(),
+    );
     let uncommon = common.reverse();
     (common, uncommon)
 }
 
-fn solve_part1<const SIZE: usize>(entries: &[Entry<SIZE>]) -> u32 {
+pub fn solve_part1(entries: &[Entry]) -> u32 {
     let (common, uncommon) = find_common_uncommon(entries);
     common.to_number() * uncommon.to_number()
 }
 
-fn filter_trendy<const SIZE: usize>(mut entries: Vec<Entry<SIZE>>, common: bool) -> u32 {
-    for i in 0..SIZE {
+fn filter_trendy(mut entries: Vec<Entry>, common: bool) -> u32 {
+    for i in 0..width(&entries) {
         if entries.len() == 1 {
             break;
         }
-        let column_common =
-            find_common_in_column(&entries, i, common.then_some(1).unwrap_or(0), common);
+        let column_common = find_common_in_column(&entries, i, u8::from(common), common);
         entries = entries
             .drain(..)
             .filter(|entry| entry.has_n_at(column_common, i))
@@ -106,15 +119,53 @@ fn filter_trendy<const SIZE: usize>(mut entries: Vec<Entry<SIZE>>, common: bool)
     entries.pop().unwrap().to_number()
 }
 
-fn solve_part2<const SIZE: usize>(entries: Vec<Entry<SIZE>>) -> u32 {
+pub fn solve_part2(entries: Vec<Entry>) -> u32 {
     let common = filter_trendy(entries.clone(), true);
     let uncommon = filter_trendy(entries, false);
     common * uncommon
 }
 
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Vec<Entry>;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    /// Entries of any width, as long as they all have the width of the first one
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        let entries: Vec<Entry> = io::read_vec(reader)?;
+        if entries.is_empty() {
+            return Err(ParseError::new("report entries", "an empty input").into());
+        }
+        let width = width(&entries);
+        match entries.iter().position(|entry| entry.width() != width) {
+            Some(index) => {
+                let text = entries[index].to_string();
+                Err(error::Error::parse_line(
+                    index + 1,
+                    &text,
+                    ParseError::new(format!("{} bits", width), text.as_str()),
+                ))
+            }
+            None => Ok(entries),
+        }
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part1(input)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part2(input.clone())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::day_03::{solve_part1, solve_part2, Entry};
+    use crate::day_03::{solve_part1, solve_part2, Day03, Entry};
+    use crate::error::ParseError;
+    use crate::solution::Solution;
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -122,18 +173,18 @@ mod test {
     #[test]
     fn example_part1() {
         let entries = vec![
-            Entry([0, 0, 1, 0, 0]),
-            Entry([1, 1, 1, 1, 0]),
-            Entry([1, 0, 1, 1, 0]),
-            Entry([1, 0, 1, 1, 1]),
-            Entry([1, 0, 1, 0, 1]),
-            Entry([0, 1, 1, 1, 1]),
-            Entry([0, 0, 1, 1, 1]),
-            Entry([1, 1, 1, 0, 0]),
-            Entry([1, 0, 0, 0, 0]),
-            Entry([1, 1, 0, 0, 1]),
-            Entry([0, 0, 0, 1, 0]),
-            Entry([0, 1, 0, 1, 0]),
+            Entry(vec![0, 0, 1, 0, 0]),
+            Entry(vec![1, 1, 1, 1, 0]),
+            Entry(vec![1, 0, 1, 1, 0]),
+            Entry(vec![1, 0, 1, 1, 1]),
+            Entry(vec![1, 0, 1, 0, 1]),
+            Entry(vec![0, 1, 1, 1, 1]),
+            Entry(vec![0, 0, 1, 1, 1]),
+            Entry(vec![1, 1, 1, 0, 0]),
+            Entry(vec![1, 0, 0, 0, 0]),
+            Entry(vec![1, 1, 0, 0, 1]),
+            Entry(vec![0, 0, 0, 1, 0]),
+            Entry(vec![0, 1, 0, 1, 0]),
         ];
 
         assert_eq!(solve_part1(&entries), 198);
@@ -141,7 +192,7 @@ mod test {
 
     #[test]
     fn part_1() -> std::io::Result<()> {
-        let entries: Vec<Entry<12>> =
+        let entries: Vec<Entry> =
             io::read_vec_from_file(&PathBuf::from_str("./inputs/day_03.txt").unwrap())?;
         println!("Day 3 part 1 result: {}", solve_part1(&entries));
         Ok(())
@@ -150,18 +201,18 @@ mod test {
     #[test]
     fn example_part2() {
         let entries = vec![
-            Entry([0, 0, 1, 0, 0]),
-            Entry([1, 1, 1, 1, 0]),
-            Entry([1, 0, 1, 1, 0]),
-            Entry([1, 0, 1, 1, 1]),
-            Entry([1, 0, 1, 0, 1]),
-            Entry([0, 1, 1, 1, 1]),
-            Entry([0, 0, 1, 1, 1]),
-            Entry([1, 1, 1, 0, 0]),
-            Entry([1, 0, 0, 0, 0]),
-            Entry([1, 1, 0, 0, 1]),
-            Entry([0, 0, 0, 1, 0]),
-            Entry([0, 1, 0, 1, 0]),
+            Entry(vec![0, 0, 1, 0, 0]),
+            Entry(vec![1, 1, 1, 1, 0]),
+            Entry(vec![1, 0, 1, 1, 0]),
+            Entry(vec![1, 0, 1, 1, 1]),
+            Entry(vec![1, 0, 1, 0, 1]),
+            Entry(vec![0, 1, 1, 1, 1]),
+            Entry(vec![0, 0, 1, 1, 1]),
+            Entry(vec![1, 1, 1, 0, 0]),
+            Entry(vec![1, 0, 0, 0, 0]),
+            Entry(vec![1, 1, 0, 0, 1]),
+            Entry(vec![0, 0, 0, 1, 0]),
+            Entry(vec![0, 1, 0, 1, 0]),
         ];
 
         assert_eq!(solve_part2(entries), 230);
@@ -169,9 +220,27 @@ mod test {
 
     #[test]
     fn part_2() -> std::io::Result<()> {
-        let entries: Vec<Entry<12>> =
+        let entries: Vec<Entry> =
             io::read_vec_from_file(&PathBuf::from_str("./inputs/day_03.txt").unwrap())?;
         println!("Day 3 part 2 result: {}", solve_part2(entries));
         Ok(())
     }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "01201".parse::<Entry>().unwrap_err(),
+            ParseError::new("a bit", "2").at_column(3)
+        );
+        let error = Day03.parse("00100\n11110\n0110".as_bytes()).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("at line 3 `0110`: expected 5 bits, found `0110`"));
+        let error = Day03.parse("".as_bytes()).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("expected report entries, found `an empty input`"));
+        let entries = Day03.parse("0110011\n1000001\n0111111".as_bytes()).unwrap();
+        assert_eq!(solve_part1(&entries), 51 * 76);
+    }
 }
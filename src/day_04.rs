@@ -1,165 +1,508 @@
+use crate::error;
+use crate::error::{Error, ParseError};
+use crate::solution::Solution;
+use crate::utils::grid::{Grid, Point};
 use std::collections::HashMap;
 use std::io::BufRead;
 
-type LotteryNumbers = Vec<usize>;
+pub type LotteryNumbers = Vec<usize>;
 
-#[derive(Clone)]
-struct Board {
-    board: Vec<Vec<usize>>,
-    checked: Vec<Vec<bool>>,
-    cache: HashMap<usize, (usize, usize)>,
+/// Along a row, down a column, then down both diagonals
+static DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// What a board needs to have marked to win
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum WinRule {
+    /// A whole row or column, or a whole diagonal of a square board
+    #[default]
+    Line,
+    /// That many consecutive marked numbers along a row, column or diagonal
+    InARow(usize),
+    /// Every number of the board
+    FullCard,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Rules {
+    pub win: WinRule,
+    /// Whether diagonals count for [`WinRule::Line`] and [`WinRule::InARow`]
+    pub diagonals: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    numbers: Grid<usize>,
+    marked: Grid<bool>,
+    cache: HashMap<usize, Point>,
 }
 
 impl Board {
-    fn from_reader(mut reader: impl BufRead, board_size: usize) -> Self {
-        let mut board = Vec::new();
-        for _ in 0..board_size {
-            let mut buff = String::new();
-            reader.read_line(&mut buff).unwrap();
-            board.push(
-                buff.trim()
-                    .split(' ')
-                    .map(|s| s.trim())
-                    .filter(|s| !s.is_empty())
-                    .map(|n| n.parse().unwrap())
-                    .collect(),
-            );
-        }
-        let mut ret = Board {
-            board,
-            checked: vec![vec![false; board_size]; board_size],
-            cache: Default::default(),
-        };
-        ret.cached();
-        ret
+    /// Board of rows of the same length
+    pub fn from_rows(rows: Vec<Vec<usize>>) -> Result<Self, ParseError> {
+        let numbers = Grid::from_rows(rows)?;
+        let cache = numbers.iter().map(|(point, &n)| (n, point)).collect();
+        Ok(Self {
+            marked: Grid::new(numbers.width(), numbers.height(), false),
+            numbers,
+            cache,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.numbers.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.numbers.height()
     }
 
-    fn cached(&mut self) {
-        for row in 0..self.board.len() {
-            for column in 0..self.board[row].len() {
-                self.cache.insert(self.board[row][column], (row, column));
+    /// Mark `number` if the board has it, returning where it is
+    fn mark(&mut self, number: usize) -> Option<Point> {
+        let point = *self.cache.get(&number)?;
+        self.marked[point] = true;
+        Some(point)
+    }
+
+    /// Cells through `point` along `direction` for which `keep` holds, from one end to the
+    /// other. `point` is in it whether `keep` holds for it or not.
+    fn segment(
+        &self,
+        point: Point,
+        (rows, columns): (isize, isize),
+        keep: impl Fn(Point) -> bool,
+    ) -> Vec<Point> {
+        let mut start = point;
+        while let Some(previous) = self.numbers.offset(start, (-rows, -columns)) {
+            if !keep(previous) {
+                break;
+            }
+            start = previous;
+        }
+        let mut cells = vec![start];
+        let mut end = start;
+        while let Some(next) = self.numbers.offset(end, (rows, columns)) {
+            // Cells up to `point` were kept walking back
+            if next != point && !keep(next) {
+                break;
             }
+            cells.push(next);
+            end = next;
         }
+        cells
     }
 
-    fn process_lottery_number(&mut self, number: usize) -> bool {
-        if let Some(&(row, column)) = self.cache.get(&number) {
-            self.checked[row][column] = true;
-            self.check_bingo_by_row_and_column(row, column)
+    /// The cells that make the board win once `point` is marked
+    fn winning_line(&self, point: Point, rules: &Rules) -> Option<Vec<Point>> {
+        let directions = if rules.diagonals {
+            &DIRECTIONS[..]
         } else {
-            false
+            &DIRECTIONS[..2]
+        };
+        match rules.win {
+            WinRule::FullCard => self
+                .marked
+                .values()
+                .all(|&marked| marked)
+                .then(|| self.numbers.points().collect()),
+            WinRule::Line => directions.iter().find_map(|&direction| {
+                let line = self.segment(point, direction, |_| true);
+                let spans = direction.0 == 0
+                    || direction.1 == 0
+                    || line.len() == self.width() && line.len() == self.height();
+                (spans && line.iter().all(|&p| self.marked[p])).then_some(line)
+            }),
+            WinRule::InARow(n) => directions.iter().find_map(|&direction| {
+                let run = self.segment(point, direction, |p| self.marked[p]);
+                (run.len() >= n).then_some(run)
+            }),
         }
     }
 
-    fn check_bingo_by_row_and_column(&self, row: usize, column: usize) -> bool {
-        self.checked[row].iter().all(|e| *e) || self.checked.iter().map(|e| e[column]).all(|e| e)
-    }
-
-    fn process_uncheck(&self) -> usize {
-        self.checked
+    pub fn unmarked_sum(&self) -> usize {
+        self.numbers
             .iter()
-            .enumerate()
-            .map(|(row, v)| {
-                v.iter()
-                    .enumerate()
-                    .map(|(column, checked)| {
-                        (!checked).then_some(self.board[row][column]).unwrap_or(0)
-                    })
-                    .sum::<usize>()
-            })
+            .filter(|&(point, _)| !self.marked[point])
+            .map(|(_, n)| n)
             .sum()
     }
 }
 
-fn read_challenge_input(
-    mut reader: impl BufRead,
-    board_size: usize,
-) -> (LotteryNumbers, Vec<Board>) {
-    let lottery_numbers = {
-        let mut buff = String::new();
-        reader.read_line(&mut buff).unwrap();
-        buff.split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .map(|n| n.parse().unwrap())
-            .collect()
-    };
+/// A board winning, boards stop playing after their win
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Win {
+    /// Index of the number in the draws
+    pub draw: usize,
+    pub number: usize,
+    /// Index of the board
+    pub board: usize,
+    /// Sum of the unmarked numbers times the number drawn
+    pub score: usize,
+    /// The marked cells the board wins with
+    pub line: Vec<Point>,
+}
+
+/// How a board fares over the draws
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Outcome {
+    /// `None` if the board never wins
+    pub win: Option<Win>,
+    /// Sum of the numbers unmarked when the board wins, or after the last draw
+    pub unmarked_sum: usize,
+}
+
+pub struct Bingo {
+    boards: Vec<Board>,
+    rules: Rules,
+}
 
-    let boards = {
-        let mut boards = Vec::new();
-        loop {
-            let mut buff = String::new();
-            if reader.read_line(&mut buff).unwrap() == 0 {
+impl Bingo {
+    pub fn new(boards: Vec<Board>, rules: Rules) -> Self {
+        Self { boards, rules }
+    }
+
+    /// Mark the draws on every board in a single pass, recording the wins as they happen,
+    /// boards winning on the same draw in board order. Boards stop playing after their win.
+    /// The unmarked sums are the ones at the win, or after the last draw.
+    fn replay(&self, draws: &[usize]) -> (Vec<Win>, Vec<usize>) {
+        let mut boards: Vec<Option<Board>> = self.boards.iter().cloned().map(Some).collect();
+        let mut unmarked_sums = vec![0; boards.len()];
+        let mut wins = Vec::new();
+        for (draw, &number) in draws.iter().enumerate() {
+            if wins.len() == boards.len() {
                 break;
             }
-            boards.push(Board::from_reader(&mut reader, board_size));
+            for (index, slot) in boards.iter_mut().enumerate() {
+                let Some(board) = slot else {
+                    continue;
+                };
+                let Some(line) = board
+                    .mark(number)
+                    .and_then(|point| board.winning_line(point, &self.rules))
+                else {
+                    continue;
+                };
+                unmarked_sums[index] = board.unmarked_sum();
+                wins.push(Win {
+                    draw,
+                    number,
+                    board: index,
+                    score: unmarked_sums[index] * number,
+                    line,
+                });
+                *slot = None;
+            }
         }
-        boards
-    };
+        for (sum, board) in unmarked_sums.iter_mut().zip(&boards) {
+            if let Some(board) = board {
+                *sum = board.unmarked_sum();
+            }
+        }
+        (wins, unmarked_sums)
+    }
+
+    /// How each board fares, in board order
+    pub fn outcomes(&self, draws: &[usize]) -> Vec<Outcome> {
+        let (wins, unmarked_sums) = self.replay(draws);
+        let mut outcomes: Vec<Outcome> = unmarked_sums
+            .into_iter()
+            .map(|unmarked_sum| Outcome {
+                win: None,
+                unmarked_sum,
+            })
+            .collect();
+        for win in wins {
+            let board = win.board;
+            outcomes[board].win = Some(win);
+        }
+        outcomes
+    }
 
-    (lottery_numbers, boards)
+    /// Every win in the order they happen, boards winning on the same draw in board order
+    pub fn play(&self, draws: &[usize]) -> Vec<Win> {
+        self.replay(draws).0
+    }
 }
 
-fn solve_part_1(lottery_numbers: LotteryNumbers, mut boards: Vec<Board>) -> usize {
-    for n in lottery_numbers {
-        for board in boards.iter_mut() {
-            if board.process_lottery_number(n) {
-                return board.process_uncheck() * n;
+/// Draws on the first line, then boards of whitespace separated numbers split by blank lines
+pub fn read_challenge_input(reader: impl BufRead) -> error::Result<(LotteryNumbers, Vec<Board>)> {
+    let mut lines = reader.lines();
+    let draws_line = lines
+        .next()
+        .ok_or_else(|| ParseError::new("lottery numbers", "an empty input"))??;
+    let lottery_numbers = draws_line
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|n| {
+            n.parse()
+                .map_err(|_| Error::parse_line(1, &draws_line, ParseError::new("a number", n)))
+        })
+        .collect::<error::Result<_>>()?;
+
+    let mut boards = Vec::new();
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        // Draws are on line 1
+        let line_number = i + 2;
+        if line.trim().is_empty() {
+            if !rows.is_empty() {
+                boards.push(Board::from_rows(std::mem::take(&mut rows)).expect("Rows checked"));
             }
+            continue;
+        }
+        let row = line
+            .split_whitespace()
+            .map(|n| {
+                n.parse().map_err(|_| {
+                    Error::parse_line(line_number, &line, ParseError::new("a number", n))
+                })
+            })
+            .collect::<error::Result<Vec<usize>>>()?;
+        if let Some(first) = rows.first().filter(|first| first.len() != row.len()) {
+            return Err(Error::parse_line(
+                line_number,
+                &line,
+                ParseError::new(
+                    format!("{} numbers", first.len()),
+                    format!("{} numbers", row.len()),
+                ),
+            ));
         }
+        rows.push(row);
     }
-    0
+    if !rows.is_empty() {
+        boards.push(Board::from_rows(rows).expect("Rows checked"));
+    }
+
+    Ok((lottery_numbers, boards))
 }
 
-fn solve_part_2(lottery_numbers: LotteryNumbers, mut boards: Vec<Board>) -> usize {
-    let mut winner: Option<(Board, usize)> = None;
-    for n in lottery_numbers {
-        boards.retain_mut(|board| {
-            if board.process_lottery_number(n) {
-                winner.replace((board.clone(), n));
-                false
-            } else {
-                true
-            }
-        });
+/// Score of the first board to win, 0 if none does
+pub fn solve_part_1(lottery_numbers: LotteryNumbers, boards: Vec<Board>) -> usize {
+    Bingo::new(boards, Rules::default())
+        .play(&lottery_numbers)
+        .first()
+        .map_or(0, |win| win.score)
+}
+
+/// Score of the last board to win, 0 if none does
+pub fn solve_part_2(lottery_numbers: LotteryNumbers, boards: Vec<Board>) -> usize {
+    Bingo::new(boards, Rules::default())
+        .play(&lottery_numbers)
+        .last()
+        .map_or(0, |win| win.score)
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = (LotteryNumbers, Vec<Board>);
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        read_challenge_input(reader)
     }
-    if let Some((board, n)) = winner {
-        board.process_uncheck() * n
-    } else {
-        0
+
+    fn part_one(&self, (lottery_numbers, boards): &Self::Input) -> Self::PartOne {
+        solve_part_1(lottery_numbers.clone(), boards.clone())
+    }
+
+    fn part_two(&self, (lottery_numbers, boards): &Self::Input) -> Self::PartTwo {
+        solve_part_2(lottery_numbers.clone(), boards.clone())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_04::{read_challenge_input, solve_part_1, solve_part_2};
+    use crate::day_04::{
+        read_challenge_input, solve_part_1, solve_part_2, Bingo, Board, Outcome, Rules, Win,
+        WinRule,
+    };
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
 
+    fn board(rows: &[&[usize]]) -> Board {
+        Board::from_rows(rows.iter().map(|row| row.to_vec()).collect()).unwrap()
+    }
+
     #[test]
     fn read_input() -> std::io::Result<()> {
         let mut reader = io::open_file_read(&PathBuf::from_str("./inputs/day_04.txt").unwrap())?;
-        let (lottery_numbers, boards) = read_challenge_input(&mut reader, 5);
+        let (lottery_numbers, boards) = read_challenge_input(&mut reader)?;
         assert!(!lottery_numbers.is_empty());
         assert!(!boards.is_empty());
         Ok(())
     }
 
+    #[test]
+    fn reject_bad_input() {
+        let error = read_challenge_input("1,2,x\n\n1 2\n3 4\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Couldn't parse input at line 1 `1,2,x`: expected a number, found `x`"
+        );
+        let error = read_challenge_input("1,2\n\n1 2\n3\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Couldn't parse input at line 4 `3`: expected 2 numbers, found `1 numbers`"
+        );
+        assert!(read_challenge_input("".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn rectangular_boards() {
+        let (draws, boards) =
+            read_challenge_input("1,2,3,6,5\n\n1 2 3\n4 5 6\n\n6 5\n3 2\n1 4\n".as_bytes())
+                .unwrap();
+        assert_eq!((boards[0].width(), boards[0].height()), (3, 2));
+        assert_eq!((boards[1].width(), boards[1].height()), (2, 3));
+        let wins = Bingo::new(boards, Rules::default()).play(&draws);
+        assert_eq!(
+            wins,
+            vec![
+                Win {
+                    draw: 2,
+                    number: 3,
+                    board: 0,
+                    score: (4 + 5 + 6) * 3,
+                    line: vec![(0, 0), (0, 1), (0, 2)],
+                },
+                Win {
+                    draw: 2,
+                    number: 3,
+                    board: 1,
+                    score: (6 + 5 + 4) * 3,
+                    line: vec![(1, 0), (1, 1)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn win_rules() {
+        let boards = vec![board(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 9]])];
+        let draws = [3, 5, 1, 7, 9, 2, 4, 6, 8];
+        let first = |rules| {
+            Bingo::new(boards.clone(), rules)
+                .play(&draws)
+                .first()
+                .cloned()
+        };
+
+        assert_eq!(first(Rules::default()).map(|win| win.draw), Some(5));
+        let diagonal = first(Rules {
+            diagonals: true,
+            ..Rules::default()
+        })
+        .unwrap();
+        assert_eq!(diagonal.draw, 3);
+        assert_eq!(diagonal.line, vec![(0, 2), (1, 1), (2, 0)]);
+        assert_eq!(diagonal.score, (2 + 4 + 6 + 8 + 9) * 7);
+
+        let two = first(Rules {
+            win: WinRule::InARow(2),
+            diagonals: false,
+        })
+        .unwrap();
+        assert_eq!((two.draw, two.line), (5, vec![(0, 0), (0, 1), (0, 2)]));
+        let two_diagonal = first(Rules {
+            win: WinRule::InARow(2),
+            diagonals: true,
+        })
+        .unwrap();
+        assert_eq!(
+            (two_diagonal.draw, two_diagonal.line),
+            (1, vec![(0, 2), (1, 1)])
+        );
+
+        let full = first(Rules {
+            win: WinRule::FullCard,
+            diagonals: false,
+        })
+        .unwrap();
+        assert_eq!((full.draw, full.score, full.line.len()), (8, 0, 9));
+    }
+
+    #[test]
+    fn rectangular_diagonals_dont_span() {
+        let boards = vec![board(&[&[1, 2, 3], &[4, 5, 6]])];
+        let rules = Rules {
+            diagonals: true,
+            ..Rules::default()
+        };
+        assert!(Bingo::new(boards, rules).play(&[1, 5]).is_empty());
+    }
+
+    #[test]
+    fn board_outcomes() {
+        let boards = vec![
+            board(&[&[1, 2], &[3, 4]]),
+            board(&[&[5, 6], &[7, 8]]),
+            board(&[&[4, 9], &[1, 3]]),
+        ];
+        let outcomes = Bingo::new(boards, Rules::default()).outcomes(&[4, 3, 5, 1]);
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome {
+                    win: Some(Win {
+                        draw: 1,
+                        number: 3,
+                        board: 0,
+                        score: 3 * 3,
+                        line: vec![(1, 0), (1, 1)],
+                    }),
+                    unmarked_sum: 1 + 2,
+                },
+                Outcome {
+                    win: None,
+                    unmarked_sum: 6 + 7 + 8,
+                },
+                Outcome {
+                    win: Some(Win {
+                        draw: 3,
+                        number: 1,
+                        board: 2,
+                        score: 9,
+                        line: vec![(1, 0), (1, 1)],
+                    }),
+                    unmarked_sum: 9,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn example_part_1() -> std::io::Result<()> {
         let mut reader =
             io::open_file_read(&PathBuf::from_str("./inputs/day_04_example.txt").unwrap())?;
-        let (lottery_numbers, boards) = read_challenge_input(&mut reader, 5);
+        let (lottery_numbers, boards) = read_challenge_input(&mut reader)?;
         let result = solve_part_1(lottery_numbers, boards);
         assert_eq!(result, 4512);
         Ok(())
     }
 
+    #[test]
+    fn example_part_2() -> std::io::Result<()> {
+        let mut reader =
+            io::open_file_read(&PathBuf::from_str("./inputs/day_04_example.txt").unwrap())?;
+        let (lottery_numbers, boards) = read_challenge_input(&mut reader)?;
+        let wins = Bingo::new(boards.clone(), Rules::default()).play(&lottery_numbers);
+        assert_eq!(
+            wins.iter().map(|win| win.board).collect::<Vec<_>>(),
+            vec![2, 0, 1]
+        );
+        assert_eq!(solve_part_2(lottery_numbers, boards), 1924);
+        Ok(())
+    }
+
     #[test]
     fn part_1() -> std::io::Result<()> {
         let mut reader = io::open_file_read(&PathBuf::from_str("./inputs/day_04.txt").unwrap())?;
-        let (lottery_numbers, boards) = read_challenge_input(&mut reader, 5);
+        let (lottery_numbers, boards) = read_challenge_input(&mut reader)?;
         let result = solve_part_1(lottery_numbers, boards);
         println!("Day 4 part 1 result: {}", result);
         Ok(())
@@ -168,7 +511,7 @@ mod test {
     #[test]
     fn part_2() -> std::io::Result<()> {
         let mut reader = io::open_file_read(&PathBuf::from_str("./inputs/day_04.txt").unwrap())?;
-        let (lottery_numbers, boards) = read_challenge_input(&mut reader, 5);
+        let (lottery_numbers, boards) = read_challenge_input(&mut reader)?;
         let result = solve_part_2(lottery_numbers, boards);
         println!("Day 4 part 2 result: {}", result);
         Ok(())
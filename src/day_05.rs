@@ -1,38 +1,45 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::Grid;
+use crate::utils::io;
 use std::collections::HashMap;
+use std::io::BufRead;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
 #[derive(Debug)]
-struct Horizontal {
-    x: RangeInclusive<usize>,
-    y: usize,
+pub struct Horizontal {
+    x: RangeInclusive<i32>,
+    y: i32,
 }
 
 #[derive(Debug)]
-struct Vertical {
-    x: usize,
-    y: RangeInclusive<usize>,
+pub struct Vertical {
+    x: i32,
+    y: RangeInclusive<i32>,
 }
 
+/// `x` and `y` go from the first end to the second, so they may be decreasing ranges
 #[derive(Debug)]
-struct Free {
-    x: RangeInclusive<usize>,
-    y: RangeInclusive<usize>,
+pub struct Free {
+    x: RangeInclusive<i32>,
+    y: RangeInclusive<i32>,
 }
 
 #[derive(Debug)]
-enum Line {
+pub enum Line {
     Horizontal(Horizontal),
     Vertical(Vertical),
     Free(Free),
 }
 
 impl FromStr for Line {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x1, y1, x2, y2) =
-            sscanf::scanf!(s, "{},{} -> {},{}", usize, usize, usize, usize).unwrap();
+        let (x1, y1, x2, y2) = sscanf::scanf!(s, "{},{} -> {},{}", i32, i32, i32, i32)
+            .ok_or_else(|| ParseError::new("`x1,y1 -> x2,y2`", s))?;
         Ok(if x1 == x2 {
             Self::Vertical(Vertical {
                 x: x1,
@@ -52,50 +59,234 @@ impl FromStr for Line {
     }
 }
 
-fn reverse_inverse_range(range: &RangeInclusive<usize>) -> Box<dyn Iterator<Item = usize>> {
-    if range.start() < range.end() {
-        Box::new(range.clone())
-    } else {
-        Box::new((*range.end()..=*range.start()).rev())
+/// A point as `(x, y)`
+pub type Position = (i32, i32);
+
+impl Line {
+    pub fn ends(&self) -> (Position, Position) {
+        match self {
+            Line::Horizontal(Horizontal { x, y }) => ((*x.start(), *y), (*x.end(), *y)),
+            Line::Vertical(Vertical { x, y }) => ((*x, *y.start()), (*x, *y.end())),
+            Line::Free(Free { x, y }) => ((*x.start(), *y.start()), (*x.end(), *y.end())),
+        }
+    }
+
+    /// Every point the line goes through, from one end to the other
+    pub fn points(&self) -> Bresenham {
+        let (from, to) = self.ends();
+        Bresenham::new(from, to)
+    }
+}
+
+/// Walk of the points closest to a segment of any slope, with integer steps only
+#[derive(Debug, Clone)]
+pub struct Bresenham {
+    // i64 so that differences of i32 coordinates don't overflow
+    current: Option<(i64, i64)>,
+    to: (i64, i64),
+    dx: i64,
+    dy: i64,
+    step: (i64, i64),
+    error: i64,
+}
+
+impl Bresenham {
+    pub fn new((x1, y1): Position, (x2, y2): Position) -> Self {
+        let (x1, y1, x2, y2) = (x1 as i64, y1 as i64, x2 as i64, y2 as i64);
+        let dx = (x2 - x1).abs();
+        let dy = -(y2 - y1).abs();
+        Self {
+            current: Some((x1, y1)),
+            to: (x2, y2),
+            dx,
+            dy,
+            step: ((x2 - x1).signum(), (y2 - y1).signum()),
+            error: dx + dy,
+        }
     }
 }
 
-fn solve(lines: &[Line], free: bool, threshold: usize) -> usize {
-    let mut grid: HashMap<(usize, usize), usize> = HashMap::new();
-    for line in lines.iter() {
-        match line {
-            Line::Horizontal(Horizontal { x, y }) => {
-                for next_x in x.clone() {
-                    *grid.entry((next_x, *y)).or_default() += 1;
-                }
+impl Iterator for Bresenham {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.current?;
+        self.current = if (x, y) == self.to {
+            None
+        } else {
+            let (mut x, mut y) = (x, y);
+            let doubled = 2 * self.error;
+            if doubled >= self.dy {
+                self.error += self.dy;
+                x += self.step.0;
             }
-            Line::Vertical(Vertical { x, y }) => {
-                for next_y in y.clone() {
-                    *grid.entry((*x, next_y)).or_default() += 1;
-                }
+            if doubled <= self.dx {
+                self.error += self.dx;
+                y += self.step.1;
             }
-            Line::Free(Free { x, y }) if free => {
-                for (next_x, next_y) in reverse_inverse_range(x).zip(reverse_inverse_range(y)) {
-                    *grid.entry((next_x, next_y)).or_default() += 1;
-                }
+            Some((x, y))
+        };
+        // Every point lies between the ends
+        Some((x as i32, y as i32))
+    }
+}
+
+/// Smallest rectangle holding some points, bounds included
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BoundingBox {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl BoundingBox {
+    pub fn of(points: impl IntoIterator<Item = Position>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => Self {
+                    min: (x, y),
+                    max: (x, y),
+                },
+                Some(Self { min, max }) => Self {
+                    min: (min.0.min(x), min.1.min(y)),
+                    max: (max.0.max(x), max.1.max(y)),
+                },
+            })
+        })
+    }
+
+    pub fn width(&self) -> u64 {
+        (self.max.0 as i64 - self.min.0 as i64) as u64 + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        (self.max.1 as i64 - self.min.1 as i64) as u64 + 1
+    }
+
+    pub fn area(&self) -> u64 {
+        self.width().saturating_mul(self.height())
+    }
+}
+
+/// Largest bounding box rasterized on a dense grid rather than a map
+const DENSE_CELLS: u64 = 1 << 21;
+
+/// Number of lines through each point
+enum Canvas {
+    Dense(BoundingBox, Grid<usize>),
+    Sparse(HashMap<Position, usize>),
+}
+
+impl Canvas {
+    fn new(bounds: Option<BoundingBox>, dense_cells: u64) -> Self {
+        match bounds {
+            Some(bounds) if bounds.area() <= dense_cells => Canvas::Dense(
+                bounds,
+                Grid::new(bounds.width() as usize, bounds.height() as usize, 0),
+            ),
+            _ => Canvas::Sparse(HashMap::new()),
+        }
+    }
+
+    fn add(&mut self, (x, y): Position) {
+        match self {
+            Canvas::Dense(bounds, grid) => {
+                let point = ((y - bounds.min.1) as usize, (x - bounds.min.0) as usize);
+                grid[point] += 1;
             }
-            _ => {}
+            Canvas::Sparse(counts) => *counts.entry((x, y)).or_default() += 1,
+        }
+    }
+
+    fn counts(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            Canvas::Dense(_, grid) => Box::new(grid.values().copied()),
+            Canvas::Sparse(counts) => Box::new(counts.values().copied()),
         }
     }
-    grid.values().filter(|v| **v >= threshold).count()
 }
 
-fn solve_part_1(lines: &[Line]) -> usize {
-    solve(lines, false, 2)
+/// What rasterizing lines covers
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Stats {
+    /// `histogram[n]` is the number of points with exactly `n` lines through them, `n > 0`
+    pub histogram: Vec<usize>,
+    /// `None` if there are no lines
+    pub bounding_box: Option<BoundingBox>,
 }
 
-fn solve_part_2(lines: &[Line]) -> usize {
-    solve(lines, true, 2)
+impl Stats {
+    /// Number of points with at least `lines` lines through them
+    pub fn overlaps(&self, lines: usize) -> usize {
+        self.histogram.iter().skip(lines.max(1)).sum()
+    }
+}
+
+/// Rasterize `lines`, skipping lines that are neither horizontal nor vertical unless `free`
+pub fn rasterize(lines: &[Line], free: bool) -> Stats {
+    rasterize_with(lines, free, DENSE_CELLS)
+}
+
+fn rasterize_with(lines: &[Line], free: bool, dense_cells: u64) -> Stats {
+    let lines: Vec<&Line> = lines
+        .iter()
+        .filter(|line| free || !matches!(line, Line::Free(_)))
+        .collect();
+    let bounding_box = BoundingBox::of(lines.iter().flat_map(|line| {
+        let (from, to) = line.ends();
+        [from, to]
+    }));
+    let mut canvas = Canvas::new(bounding_box, dense_cells);
+    for line in lines {
+        line.points().for_each(|point| canvas.add(point));
+    }
+
+    let mut histogram = vec![0];
+    for count in canvas.counts().filter(|&count| count > 0) {
+        if histogram.len() <= count {
+            histogram.resize(count + 1, 0);
+        }
+        histogram[count] += 1;
+    }
+    Stats {
+        histogram,
+        bounding_box,
+    }
+}
+
+pub fn solve_part_1(lines: &[Line]) -> usize {
+    rasterize(lines, false).overlaps(2)
+}
+
+pub fn solve_part_2(lines: &[Line]) -> usize {
+    rasterize(lines, true).overlaps(2)
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Vec<Line>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_vec(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part_1(input)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_05::{solve_part_1, solve_part_2, Line};
+    use crate::day_05::{
+        rasterize, rasterize_with, solve_part_1, solve_part_2, BoundingBox, Line, Stats,
+    };
+    use crate::error::ParseError;
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -131,4 +322,63 @@ mod test {
         println!("Day 5 part 2 solution: {}", solve_part_2(&lines));
         Ok(())
     }
+
+    #[test]
+    fn any_slope() {
+        let line: Line = "0,0 -> 5,2".parse().unwrap();
+        assert_eq!(
+            line.points().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]
+        );
+        let line: Line = "1,-1 -> -2,5".parse().unwrap();
+        let points: Vec<_> = line.points().collect();
+        assert_eq!((points[0], points[6]), ((1, -1), (-2, 5)));
+        assert_eq!(points.len(), 7);
+        let line: Line = "3,3 -> 0,0".parse().unwrap();
+        assert_eq!(
+            line.points().collect::<Vec<_>>(),
+            vec![(3, 3), (2, 2), (1, 1), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn stats() -> std::io::Result<()> {
+        let lines: Vec<Line> =
+            io::read_vec_from_file(&PathBuf::from_str("./inputs/day_05_example.txt").unwrap())?;
+        let stats = rasterize(&lines, true);
+        assert_eq!(
+            stats,
+            Stats {
+                histogram: vec![0, 27, 10, 2],
+                bounding_box: Some(BoundingBox {
+                    min: (0, 0),
+                    max: (9, 9)
+                }),
+            }
+        );
+        assert_eq!(stats.overlaps(3), 2);
+        assert_eq!(rasterize_with(&lines, true, 0), stats);
+        assert_eq!(rasterize_with(&lines, false, 0), rasterize(&lines, false));
+
+        let far: Vec<Line> = ["-2000000000,0 -> 2000000000,0", "0,-5 -> 0,5"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let stats = rasterize(&far[1..], false);
+        assert_eq!(stats.histogram, vec![0, 11]);
+        assert_eq!(
+            BoundingBox::of(far.iter().flat_map(|line| [line.ends().0, line.ends().1]))
+                .map(|bounds| bounds.width()),
+            Some(4_000_000_001)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(
+            "0,9 -> 5".parse::<Line>().unwrap_err(),
+            ParseError::new("`x1,y1 -> x2,y2`", "0,9 -> 5")
+        );
+    }
 }
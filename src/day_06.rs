@@ -1,70 +1,236 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
+use crate::utils::num::Natural;
+use num_bigint::BigUint;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
 
 #[derive(Debug)]
-struct LanternFish<const BASE: usize> {
+pub struct LanternFish<const BASE: usize> {
     counter: usize,
 }
 
 impl<const BASE: usize> LanternFish<BASE> {
-    fn new(base_counter: usize) -> Self {
+    pub fn new(base_counter: usize) -> Self {
         Self {
             counter: base_counter,
         }
     }
-
-    fn dec(&mut self) -> bool {
-        if self.counter == 0 {
-            self.counter = BASE;
-            true
-        } else {
-            self.counter -= 1;
-            false
-        }
-    }
 }
 
 impl<const BASE: usize> FromStr for LanternFish<BASE> {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            counter: s.parse().map_err(|_| ())?,
+            counter: s.parse().map_err(|_| ParseError::new("a timer", s))?,
         })
     }
 }
 
-fn solve_part_1<const BASE: usize, const EXTRA: usize>(
-    mut fish: Vec<LanternFish<BASE>>,
+/// Why a population can't be forecast
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ForecastError {
+    /// A fish timer above the longest timer of the model
+    Timer { timer: usize, max: usize },
+    /// A count too large for the numbers the forecast runs on
+    Overflow,
+}
+
+impl Display for ForecastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForecastError::Timer { timer, max } => {
+                write!(f, "timer {} is above the longest timer {}", timer, max)
+            }
+            ForecastError::Overflow => write!(f, "the population overflows"),
+        }
+    }
+}
+
+impl std::error::Error for ForecastError {}
+
+/// Square matrix of counts
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Matrix<N>(Vec<Vec<N>>);
+
+impl<N: Natural> Matrix<N> {
+    fn identity(size: usize) -> Self {
+        Self(
+            (0..size)
+                .map(|row| {
+                    (0..size)
+                        .map(|column| if row == column { N::one() } else { N::zero() })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    fn dot(row: &[N], column: impl Iterator<Item = N>) -> Option<N> {
+        row.iter().zip(column).try_fold(N::zero(), |sum, (a, b)| {
+            sum.checked_add(&a.checked_mul(&b)?)
+        })
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let size = self.0.len();
+        self.0
+            .iter()
+            .map(|row| {
+                (0..size)
+                    .map(|column| Self::dot(row, other.0.iter().map(|r| r[column].clone())))
+                    .collect()
+            })
+            .collect::<Option<_>>()
+            .map(Self)
+    }
+
+    fn checked_pow(&self, mut exponent: u64) -> Option<Self> {
+        let mut result = Self::identity(self.0.len());
+        let mut square = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.checked_mul(&square)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.checked_mul(&square)?;
+            }
+        }
+        Some(result)
+    }
+
+    fn apply(&self, vector: &[N]) -> Option<Vec<N>> {
+        self.0
+            .iter()
+            .map(|row| Self::dot(row, vector.iter().cloned()))
+            .collect()
+    }
+}
+
+/// Lanternfish counted by timer. A fish whose timer is 0 gets its timer back to `reset`
+/// the next day and a newborn with a timer of `newborn`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Population {
+    reset: usize,
+    newborn: usize,
+}
+
+impl Population {
+    pub fn new(reset: usize, newborn: usize) -> Self {
+        Self { reset, newborn }
+    }
+
+    /// Longest timer a fish can have
+    pub fn max_timer(&self) -> usize {
+        self.reset.max(self.newborn)
+    }
+
+    /// How counts by timer move in a day, `next = transition × today`
+    fn transition<N: Natural>(&self) -> Matrix<N> {
+        let size = self.max_timer() + 1;
+        let mut matrix = Matrix(vec![vec![N::zero(); size]; size]);
+        for timer in 0..size - 1 {
+            matrix.0[timer][timer + 1] = N::one();
+        }
+        for timer in [self.reset, self.newborn] {
+            let cell = &mut matrix.0[timer][0];
+            *cell = cell.checked_add(&N::one()).expect("Two at most");
+        }
+        matrix
+    }
+
+    /// Number of fish after `days` from fish with `timers`, in O(log(days)) matrix products.
+    /// Only the counts of fish are checked: when the matrix power overflows `N` while the
+    /// counts may not, the days are stepped one by one. A population at least doubles every
+    /// `max_timer() + 1` days, so that only takes long for numbers that don't overflow.
+    pub fn forecast<N: Natural>(&self, timers: &[usize], days: u64) -> Result<N, ForecastError> {
+        let max = self.max_timer();
+        let mut counts = vec![N::zero(); max + 1];
+        for &timer in timers {
+            let count = counts
+                .get_mut(timer)
+                .ok_or(ForecastError::Timer { timer, max })?;
+            *count = count
+                .checked_add(&N::one())
+                .ok_or(ForecastError::Overflow)?;
+        }
+        if timers.is_empty() {
+            return Ok(N::zero());
+        }
+        let transition = self.transition();
+        let counts = match transition
+            .checked_pow(days)
+            .and_then(|matrix| matrix.apply(&counts))
+        {
+            Some(counts) => counts,
+            None => {
+                for _ in 0..days {
+                    counts = transition.apply(&counts).ok_or(ForecastError::Overflow)?;
+                }
+                counts
+            }
+        };
+        counts
+            .iter()
+            .try_fold(N::zero(), |sum, count| sum.checked_add(count))
+            .ok_or(ForecastError::Overflow)
+    }
+}
+
+pub fn solve_part_1<const BASE: usize, const EXTRA: usize>(
+    fish: Vec<LanternFish<BASE>>,
     iter: usize,
-) -> usize {
-    for _ in 0..iter {
-        let new_fish = fish
-            .iter_mut()
-            .map(|fish| fish.dec())
-            .filter(|e| matches!(e, true))
-            .count();
-        fish.extend((0..new_fish).map(|_| LanternFish::<BASE>::new(BASE + EXTRA)));
-    }
-    fish.len()
+) -> Result<usize, ForecastError> {
+    let timers: Vec<usize> = fish.iter().map(|fish| fish.counter).collect();
+    Population::new(BASE, BASE + EXTRA).forecast(&timers, iter as u64)
 }
 
-fn solve_part_2(fishes: Vec<usize>, days: usize) -> usize {
-    let mut fish_days = vec![0usize; 9];
-    for fish in &fishes {
-        fish_days[*fish] += 1;
+pub fn solve_part_2(fishes: Vec<usize>, days: usize) -> Result<usize, ForecastError> {
+    Population::new(6, 8).forecast(&fishes, days as u64)
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = Vec<usize>;
+    /// Counted with big numbers, the population can't overflow them
+    type PartOne = BigUint;
+    type PartTwo = BigUint;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        let timers: Vec<usize> = io::read_separated_vec(reader, ',')?;
+        let max = Population::new(6, 8).max_timer();
+        match timers.iter().find(|&&timer| timer > max) {
+            Some(timer) => {
+                Err(ParseError::new(format!("a timer up to {}", max), timer.to_string()).into())
+            }
+            None => Ok(timers),
+        }
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        Population::new(6, 8)
+            .forecast(input, 80)
+            .expect("Timers checked by `Day06::parse`")
     }
-    for _ in 0usize..days {
-        let fish_0 = fish_days.remove(0);
-        fish_days.push(fish_0);
-        fish_days[6] += fish_days[8];
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        Population::new(6, 8)
+            .forecast(input, 256)
+            .expect("Timers checked by `Day06::parse`")
     }
-    fish_days.iter().sum()
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_06::{solve_part_1, solve_part_2, LanternFish};
+    use crate::day_06::{solve_part_1, solve_part_2, ForecastError, LanternFish, Population};
     use crate::utils::io;
+    use num_bigint::BigUint;
     use std::io::Read;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -80,7 +246,66 @@ mod test {
             LanternFish::new(1),
             LanternFish::new(2),
         ];
-        assert_eq!(solve_part_1::<BASE, EXTRA>(fish, 80), 5934);
+        assert_eq!(solve_part_1::<BASE, EXTRA>(fish, 80), Ok(5934));
+    }
+
+    /// Day by day count of fish by timer
+    fn simulate(population: Population, timers: &[usize], days: u64) -> u128 {
+        let mut counts = vec![0u128; population.max_timer() + 1];
+        timers.iter().for_each(|&timer| counts[timer] += 1);
+        for _ in 0..days {
+            counts.rotate_left(1);
+            let parents = counts[population.max_timer()];
+            counts[population.max_timer()] = 0;
+            counts[population.reset] += parents;
+            counts[population.newborn] += parents;
+        }
+        counts.iter().sum()
+    }
+
+    #[test]
+    fn forecast_matches_simulation() {
+        let timers = [3, 4, 3, 1, 2];
+        for (reset, newborn) in [(6, 8), (2, 3), (4, 1), (0, 0)] {
+            let population = Population::new(reset, newborn);
+            let every_timer: Vec<usize> = (0..=population.max_timer()).collect();
+            for days in [0, 1, 7, 18, 80, 100] {
+                assert_eq!(
+                    population.forecast::<u128>(&every_timer, days),
+                    Ok(simulate(population, &every_timer, days)),
+                    "reset {}, newborn {}, {} days",
+                    reset,
+                    newborn,
+                    days
+                );
+            }
+        }
+        let population = Population::new(6, 8);
+        assert_eq!(population.forecast::<u64>(&timers, 18), Ok(26));
+        assert_eq!(population.forecast::<u64>(&timers, 256), Ok(26984457539));
+    }
+
+    #[test]
+    fn forecast_errors() {
+        let population = Population::new(6, 8);
+        assert_eq!(
+            population.forecast::<u64>(&[9], 1),
+            Err(ForecastError::Timer { timer: 9, max: 8 })
+        );
+        assert_eq!(
+            population.forecast::<u64>(&[3], 1_000_000),
+            Err(ForecastError::Overflow)
+        );
+        assert_eq!(population.forecast::<u64>(&[], 1_000_000), Ok(0));
+        let big = population.forecast::<BigUint>(&[3], 100_000).unwrap();
+        assert_eq!(big.to_string().len(), 3784);
+        // The matrix for 63 days overflows a `u32` but not the count of a fish with timer 1
+        let population = Population::new(1, 1);
+        assert_eq!(population.forecast::<u32>(&[1], 63), Ok(1 << 31));
+        assert_eq!(
+            population.forecast::<u32>(&[1], 64),
+            Err(ForecastError::Overflow)
+        );
     }
 
     #[test]
@@ -94,7 +319,7 @@ mod test {
             reader.read_to_string(&mut buff)?;
             buff.trim().split(',').map(|s| s.parse().unwrap()).collect()
         };
-        let result = solve_part_1::<BASE, EXTRA>(fish, 80);
+        let result = solve_part_1::<BASE, EXTRA>(fish, 80).unwrap();
         println!("Day 6 part 1 result: {}", result);
         Ok(())
     }
@@ -108,7 +333,7 @@ mod test {
             reader.read_to_string(&mut buff)?;
             buff.trim().split(',').map(|s| s.parse().unwrap()).collect()
         };
-        let result = solve_part_2(fish, 256);
+        let result = solve_part_2(fish, 256).unwrap();
         println!("Day 6 part 2 result: {}", result);
         Ok(())
     }
@@ -1,32 +1,135 @@
-use std::collections::HashMap;
-
-fn solve(positions: &[usize], mut calc_fuel: impl FnMut(usize) -> usize) -> (usize, usize) {
-    let max = *positions.iter().max().unwrap();
-    let mut map: HashMap<usize, usize> = (0..max).zip(std::iter::repeat(0usize)).collect();
-    for &a in positions {
-        for b in 0..max {
-            let diff = calc_fuel(a.abs_diff(b));
-            map.entry(b).and_modify(|e| *e += diff);
+use crate::error;
+use crate::solution::Solution;
+use crate::utils::io;
+use std::cmp::Ordering;
+use std::io::BufRead;
+
+/// How much fuel a crab burns to move some distance
+#[derive(Clone, Copy)]
+pub enum Cost<'f> {
+    /// One unit per step, a median of the positions is best
+    Linear,
+    /// One more unit for each step than for the previous one, the best position is less
+    /// than a step away from the mean
+    Triangular,
+    /// Any convex and non decreasing cost, found with a ternary search
+    Convex(&'f dyn Fn(usize) -> usize),
+    /// Any non decreasing cost, every position between the crabs is tried
+    Any(&'f dyn Fn(usize) -> usize),
+}
+
+impl Cost<'_> {
+    pub fn fuel(&self, distance: usize) -> usize {
+        match self {
+            Cost::Linear => distance,
+            Cost::Triangular => distance * (distance + 1) / 2,
+            Cost::Convex(cost) | Cost::Any(cost) => cost(distance),
+        }
+    }
+}
+
+/// Where crabs meet and the fuel they burn to get there
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Alignment {
+    pub position: usize,
+    pub fuel: usize,
+}
+
+fn total_fuel(positions: &[usize], cost: &Cost, position: usize) -> usize {
+    positions
+        .iter()
+        .map(|&crab| cost.fuel(crab.abs_diff(position)))
+        .sum()
+}
+
+/// The cheapest of `candidates`, the first one on ties
+fn cheapest(
+    positions: &[usize],
+    cost: &Cost,
+    candidates: impl Iterator<Item = usize>,
+) -> Option<Alignment> {
+    candidates
+        .map(|position| Alignment {
+            position,
+            fuel: total_fuel(positions, cost, position),
+        })
+        .min_by_key(|alignment| alignment.fuel)
+}
+
+/// Ternary search of the minimum of a convex function over `lo..=hi`
+fn ternary_search(mut lo: usize, mut hi: usize, mut f: impl FnMut(usize) -> usize) -> usize {
+    while hi - lo > 2 {
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - third);
+        match f(m1).cmp(&f(m2)) {
+            Ordering::Less => hi = m2 - 1,
+            Ordering::Greater => lo = m1 + 1,
+            // Convex, so a minimum lies between them
+            Ordering::Equal => (lo, hi) = (m1, m2),
+        }
+    }
+    (lo..=hi).min_by_key(|&x| f(x)).expect("lo <= hi")
+}
+
+/// Cheapest position for the crabs to meet at, `None` if there are no crabs.
+/// The best positions lie between the crabs for non decreasing costs, and only those are tried.
+pub fn align(positions: &[usize], cost: &Cost) -> Option<Alignment> {
+    let (&min, &max) = (positions.iter().min()?, positions.iter().max()?);
+    match cost {
+        Cost::Linear => {
+            let mut sorted = positions.to_vec();
+            let (_, &mut median, _) = sorted.select_nth_unstable((positions.len() - 1) / 2);
+            cheapest(positions, cost, std::iter::once(median))
         }
+        Cost::Triangular => {
+            let mean = positions.iter().sum::<usize>() / positions.len();
+            cheapest(positions, cost, mean..=(mean + 1).min(max))
+        }
+        Cost::Convex(_) => {
+            let position = ternary_search(min, max, |p| total_fuel(positions, cost, p));
+            cheapest(positions, cost, std::iter::once(position))
+        }
+        Cost::Any(_) => cheapest(positions, cost, min..=max),
     }
-    map.iter()
-        .min_by_key(|(_pos, fuel)| **fuel)
-        .map(|(&pos, &fuel)| (pos, fuel))
-        .unwrap()
 }
 
-fn solve_part_1(positions: &[usize]) -> (usize, usize) {
-    solve(positions, |i| i)
+pub fn solve_part_1(positions: &[usize]) -> (usize, usize) {
+    let Alignment { position, fuel } = align(positions, &Cost::Linear).expect("Some crabs");
+    (position, fuel)
 }
 
-fn solve_part_2(positions: &[usize]) -> (usize, usize) {
-    solve(positions, |i| i * (i + 1) / 2)
+pub fn solve_part_2(positions: &[usize]) -> (usize, usize) {
+    let Alignment { position, fuel } = align(positions, &Cost::Triangular).expect("Some crabs");
+    (position, fuel)
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = Vec<usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_separated_vec(reader, ',')
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        let (_pos, fuel) = solve_part_1(input);
+        fuel
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        let (_pos, fuel) = solve_part_2(input);
+        fuel
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_07::{solve_part_1, solve_part_2};
+    use crate::day_07::{align, solve_part_1, solve_part_2, Alignment, Cost};
     use crate::utils::io;
+    use proptest::prelude::*;
     use std::io::Read;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -37,6 +140,40 @@ mod test {
         assert_eq!(solve_part_1(&positions), (2, 37))
     }
 
+    proptest! {
+        #[test]
+        fn searches_match_brute_force(positions in prop::collection::vec(0usize..1000, 1..50)) {
+            let square = |d: usize| d * d;
+            let triangular = |d: usize| d * (d + 1) / 2;
+            let fuel = |cost| align(&positions, &cost).unwrap().fuel;
+            prop_assert_eq!(fuel(Cost::Linear), fuel(Cost::Any(&|d| d)));
+            prop_assert_eq!(fuel(Cost::Triangular), fuel(Cost::Any(&triangular)));
+            prop_assert_eq!(fuel(Cost::Convex(&triangular)), fuel(Cost::Any(&triangular)));
+            prop_assert_eq!(fuel(Cost::Convex(&square)), fuel(Cost::Any(&square)));
+        }
+    }
+
+    #[test]
+    fn align_edges() {
+        assert_eq!(align(&[], &Cost::Linear), None);
+        assert_eq!(
+            align(&[5], &Cost::Triangular),
+            Some(Alignment {
+                position: 5,
+                fuel: 0
+            })
+        );
+        // Free up to two steps, a single unit further away
+        let step = |d: usize| usize::from(d >= 3);
+        assert_eq!(
+            align(&[0, 1, 5, 6, 7], &Cost::Any(&step)),
+            Some(Alignment {
+                position: 5,
+                fuel: 2
+            })
+        );
+    }
+
     #[test]
     fn part_1() -> std::io::Result<()> {
         let positions: Vec<usize> = {
@@ -1,29 +1,14 @@
-use std::collections::{HashMap, HashSet};
-use std::lazy::Lazy;
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
+use std::sync::LazyLock;
 
-type Digit = HashSet<char>;
-
-const DIGITS: Lazy<[Digit; 10]> = Lazy::new(|| {
-    [
-        "abcefg".chars().collect(),
-        "cf".chars().collect(),
-        "acdeg".chars().collect(),
-        "acdfg".chars().collect(),
-        "bcdf".chars().collect(),
-        "abdfg".chars().collect(),
-        "abdefg".chars().collect(),
-        "acf".chars().collect(),
-        "abcdefg".chars().collect(),
-        "abcdfg".chars().collect(),
-    ]
-});
-
-const NUMBERS: Lazy<HashMap<String, usize>> =
-    Lazy::new(|| DIGITS.iter().map(digit_key).zip(0usize..).collect());
-
-const SIZES: Lazy<[usize; 10]> = Lazy::new(|| DIGITS.clone().map(|set| set.len()));
-
+/// Segments lit by each digit of a seven-segment display
 //  aaaa
 // b    c
 // b    c
@@ -31,201 +16,337 @@ const SIZES: Lazy<[usize; 10]> = Lazy::new(|| DIGITS.clone().map(|set| set.len()
 // e    f
 // e    f
 //  gggg
-fn reverse_count(inputs: &[Digit]) -> HashMap<usize, char> {
-    "abcdefg"
-        .chars()
-        .map(|c| (inputs.iter().filter(|d| d.contains(&c)).count(), c))
-        .collect()
+pub const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+static SEVEN_SEGMENT: LazyLock<Font> =
+    LazyLock::new(|| Font::new(&DIGITS).expect("Distinct digits"));
+
+/// Segments lit by each digit
+const SIZES: [usize; 10] = [6, 2, 5, 5, 4, 5, 6, 3, 7, 6];
+
+/// Glyphs of a display, as the segments each one lights. Segments are named by characters,
+/// and glyphs by their index.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Font {
+    segments: Vec<char>,
+    /// Bit `i` stands for `segments[i]`
+    glyphs: Vec<u64>,
 }
 
-fn letter_by_count(
-    letter: char,
-    count: usize,
-    by_count: &HashMap<usize, char>,
-    mapping: &mut HashMap<char, char>,
-    inverse_mapping: &mut HashMap<char, char>,
-) {
-    let remap = by_count[&count];
-    mapping.insert(letter, remap);
-    inverse_mapping.insert(remap, letter);
-}
+impl Font {
+    /// Font of distinct glyphs using from 1 to 64 segments
+    pub fn new(glyphs: &[&str]) -> Result<Self, ParseError> {
+        let mut segments: Vec<char> = glyphs.iter().flat_map(|glyph| glyph.chars()).collect();
+        segments.sort_unstable();
+        segments.dedup();
+        if segments.is_empty() {
+            return Err(ParseError::new("at least one segment", "no segments"));
+        }
+        if segments.len() > 64 {
+            return Err(ParseError::new(
+                "up to 64 segments",
+                format!("{} segments", segments.len()),
+            ));
+        }
+        let mut font = Self {
+            segments,
+            glyphs: Vec::new(),
+        };
+        for glyph in glyphs {
+            let mask = font.mask(glyph).expect("Segments of the glyphs");
+            if font.glyphs.contains(&mask) {
+                return Err(ParseError::new("distinct glyphs", *glyph));
+            }
+            font.glyphs.push(mask);
+        }
+        Ok(font)
+    }
+
+    fn mask(&self, segments: &str) -> Option<u64> {
+        segments.chars().try_fold(0, |mask, segment| {
+            let i = self.segments.iter().position(|&s| s == segment)?;
+            Some(mask | 1 << i)
+        })
+    }
+
+    fn all_segments(&self) -> u64 {
+        u64::MAX >> (64 - self.segments.len())
+    }
+
+    /// Glyph lit by `pattern` once its wires are mapped to segments by `wiring`
+    pub fn decode(&self, wiring: &Wiring, pattern: &str) -> Option<usize> {
+        let segments: String = pattern
+            .chars()
+            .map(|wire| wiring.segment(wire))
+            .collect::<Option<_>>()?;
+        let mask = self.mask(&segments)?;
+        self.glyphs.iter().position(|&glyph| glyph == mask)
+    }
+
+    /// The wiring of the segments to wires named after them, under which every observed
+    /// pattern lights a glyph. Wires are narrowed down by the sizes of the glyphs they can be
+    /// part of, then assigned one by one, dropping assignments that leave a pattern no glyph.
+    pub fn solve(&self, observations: &[&str]) -> Result<Wiring, WiringError> {
+        let size = self.segments.len();
+        let mut patterns: Vec<u64> = observations
+            .iter()
+            .map(|pattern| {
+                pattern.chars().try_fold(0, |mask, wire| {
+                    let i = self.segments.iter().position(|&s| s == wire);
+                    Ok(mask | 1 << i.ok_or(WiringError::UnknownWire(wire))?)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        patterns.sort_unstable();
+        patterns.dedup();
+
+        let all = self.all_segments();
+        let mut candidates = vec![all; size];
+        for &pattern in &patterns {
+            let (lit, unlit) = self
+                .glyphs_like(pattern)
+                .fold((0, 0), |(lit, unlit), glyph| {
+                    (lit | glyph, unlit | (all & !glyph))
+                });
+            for (wire, candidate) in candidates.iter_mut().enumerate() {
+                *candidate &= if pattern & 1 << wire != 0 { lit } else { unlit };
+            }
+        }
 
-fn rule_f(
-    by_count: &HashMap<usize, char>,
-    mapping: &mut HashMap<char, char>,
-    inverse_mapping: &mut HashMap<char, char>,
-) {
-    letter_by_count('f', 9, by_count, mapping, inverse_mapping);
+        let mut order: Vec<usize> = (0..size).collect();
+        order.sort_by_key(|&wire| candidates[wire].count_ones());
+        let mut search = Search {
+            font: self,
+            patterns: &patterns,
+            candidates: &candidates,
+            order: &order,
+            assigned: vec![None; size],
+            solutions: Vec::new(),
+        };
+        search.assign(0, 0);
+
+        let mut solutions = search.solutions.into_iter().map(|segments| Wiring {
+            wires: self
+                .segments
+                .iter()
+                .zip(segments)
+                .map(|(&wire, segment)| (wire, self.segments[segment]))
+                .collect(),
+        });
+        match (solutions.next(), solutions.next()) {
+            (None, _) => Err(WiringError::Impossible),
+            (Some(wiring), None) => Ok(wiring),
+            (Some(first), Some(second)) => Err(WiringError::Ambiguous(first, second)),
+        }
+    }
+
+    /// Glyphs lighting as many segments as `pattern` has wires
+    fn glyphs_like(&self, pattern: u64) -> impl Iterator<Item = u64> + '_ {
+        self.glyphs
+            .iter()
+            .copied()
+            .filter(move |glyph| glyph.count_ones() == pattern.count_ones())
+    }
 }
 
-fn rule_e(
-    by_count: &HashMap<usize, char>,
-    mapping: &mut HashMap<char, char>,
-    inverse_mapping: &mut HashMap<char, char>,
-) {
-    letter_by_count('e', 4, by_count, mapping, inverse_mapping);
+/// Backtracking state of [`Font::solve`], wires and segments are indexes in the font
+struct Search<'a> {
+    font: &'a Font,
+    patterns: &'a [u64],
+    candidates: &'a [u64],
+    /// Wires in the order they are assigned
+    order: &'a [usize],
+    assigned: Vec<Option<usize>>,
+    /// Segment of each wire, up to two solutions to tell ambiguous wirings
+    solutions: Vec<Vec<usize>>,
 }
 
-fn rule_b(
-    by_count: &HashMap<usize, char>,
-    mapping: &mut HashMap<char, char>,
-    inverse_mapping: &mut HashMap<char, char>,
-) {
-    letter_by_count('b', 6, by_count, mapping, inverse_mapping);
+impl Search<'_> {
+    fn assign(&mut self, depth: usize, used: u64) {
+        if self.solutions.len() >= 2 {
+            return;
+        }
+        let Some(&wire) = self.order.get(depth) else {
+            let segments = self
+                .assigned
+                .iter()
+                .map(|s| s.expect("Every wire"))
+                .collect();
+            self.solutions.push(segments);
+            return;
+        };
+        let free = self.candidates[wire] & !used;
+        for segment in (0..self.font.segments.len()).filter(|s| free & 1 << s != 0) {
+            self.assigned[wire] = Some(segment);
+            if self.consistent() {
+                self.assign(depth + 1, used | 1 << segment);
+            }
+        }
+        self.assigned[wire] = None;
+    }
+
+    /// Whether every pattern can still light a glyph
+    fn consistent(&self) -> bool {
+        self.patterns.iter().all(|&pattern| {
+            let (lit, unlit) = self.assigned.iter().enumerate().fold(
+                (0u64, 0u64),
+                |(lit, unlit), (wire, segment)| match segment {
+                    Some(segment) if pattern & 1 << wire != 0 => (lit | 1 << segment, unlit),
+                    Some(segment) => (lit, unlit | 1 << segment),
+                    None => (lit, unlit),
+                },
+            );
+            self.font
+                .glyphs_like(pattern)
+                .any(|glyph| lit & !glyph == 0 && unlit & glyph == 0)
+        })
+    }
 }
 
-fn rule_feb(
-    by_count: &HashMap<usize, char>,
-    mapping: &mut HashMap<char, char>,
-    inverse_mapping: &mut HashMap<char, char>,
-) {
-    rule_f(by_count, mapping, inverse_mapping);
-    rule_e(by_count, mapping, inverse_mapping);
-    rule_b(by_count, mapping, inverse_mapping);
+/// Segment each wire is connected to
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Wiring {
+    wires: BTreeMap<char, char>,
 }
 
-fn rule_c(
-    inputs: &[Digit],
-    mapping: &mut HashMap<char, char>,
-    inverse_mapping: &mut HashMap<char, char>,
-) {
-    let one = inputs.iter().find(|d| d.len() == SIZES[1]).unwrap();
-    let c = one
-        .difference(&[mapping[&'f']].into_iter().collect())
-        .copied()
-        .next()
-        .unwrap();
-
-    mapping.insert('c', c);
-    inverse_mapping.insert(c, 'c');
+impl Wiring {
+    pub fn segment(&self, wire: char) -> Option<char> {
+        self.wires.get(&wire).copied()
+    }
 }
 
-fn rule_a(
-    inputs: &[Digit],
-    mapping: &mut HashMap<char, char>,
-    reverse_mapping: &mut HashMap<char, char>,
-) {
-    let seven = inputs.iter().find(|d| d.len() == SIZES[7]).unwrap();
-    let c = mapping[&'c'];
-    let f = mapping[&'f'];
-    let set: Digit = [c, f].into_iter().collect();
-    let a = seven.difference(&set).collect::<Vec<_>>().pop().unwrap();
-    mapping.insert('a', *a);
-    reverse_mapping.insert(*a, 'a');
+/// As `wire->segment` pairs
+impl Display for Wiring {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let pairs: Vec<String> = self
+            .wires
+            .iter()
+            .map(|(wire, segment)| format!("{}->{}", wire, segment))
+            .collect();
+        write!(f, "{}", pairs.join(" "))
+    }
 }
 
-fn rule_d(
-    inputs: &[Digit],
-    mapping: &mut HashMap<char, char>,
-    reverse_mapping: &mut HashMap<char, char>,
-) {
-    let checked: Digit = "acf".chars().map(|c| mapping[&c]).collect();
-    let subset = inputs
-        .iter()
-        .filter_map(|d| {
-            let diff: Digit = d.difference(&checked).copied().collect();
-            if diff.len() == 2 {
-                Some(diff)
-            } else {
-                None
-            }
-        })
-        .reduce(|s1, s2| s1.intersection(&s2).copied().collect())
-        .unwrap();
-    let d = subset.iter().next().unwrap();
-    mapping.insert('d', *d);
-    reverse_mapping.insert(*d, 'd');
+/// Why observations don't tell how a display is wired
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum WiringError {
+    /// A wire named after no segment of the font
+    UnknownWire(char),
+    /// No wiring makes every pattern a glyph
+    Impossible,
+    /// Several wirings do, here are two of them
+    Ambiguous(Wiring, Wiring),
 }
 
-fn rule_g(
-    inputs: &[Digit],
-    mapping: &mut HashMap<char, char>,
-    reverse_mapping: &mut HashMap<char, char>,
-) {
-    let to_diff: Digit = reverse_mapping.keys().copied().collect();
-    let nine = inputs
-        .iter()
-        .filter_map(|d| {
-            let res: Digit = d.difference(&to_diff).copied().collect();
-            if res.len() == 1 {
-                Some(res)
-            } else {
-                None
+impl Display for WiringError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WiringError::UnknownWire(wire) => write!(f, "wire {} isn't a segment", wire),
+            WiringError::Impossible => write!(f, "no wiring fits the patterns"),
+            WiringError::Ambiguous(first, second) => {
+                write!(f, "both `{}` and `{}` fit the patterns", first, second)
             }
-        })
-        .next()
-        .unwrap();
-    let g = nine.iter().next().unwrap();
-    mapping.insert('g', *g);
-    reverse_mapping.insert(*g, 'g');
+        }
+    }
 }
 
-fn build_mappings(inputs: &[Digit]) -> (HashMap<char, char>, HashMap<char, char>) {
-    let mut mapping = HashMap::new();
-    let mut reverse_mapping = HashMap::new();
-    let by_count = reverse_count(inputs);
-    rule_feb(&by_count, &mut mapping, &mut reverse_mapping);
-    rule_c(inputs, &mut mapping, &mut reverse_mapping);
-    rule_a(inputs, &mut mapping, &mut reverse_mapping);
-    rule_d(inputs, &mut mapping, &mut reverse_mapping);
-    rule_g(inputs, &mut mapping, &mut reverse_mapping);
-    (mapping, reverse_mapping)
-}
+impl std::error::Error for WiringError {}
 
-fn remap_digit(digit: &Digit, mapping: &HashMap<char, char>) -> Digit {
-    digit.iter().map(|c| mapping[c]).collect()
+#[derive(Debug, Clone)]
+pub struct Io {
+    input: Vec<String>,
+    output: Vec<String>,
 }
 
-fn digits_to_num(digits: &[Digit]) -> usize {
-    digits
-        .iter()
-        .map(digit_key)
-        .map(|k| NUMBERS[&k])
-        .map(|n| n.to_string())
-        .collect::<Vec<String>>()
-        .join("")
-        .parse()
-        .unwrap()
+impl Display for Io {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | {}", self.input.join(" "), self.output.join(" "))
+    }
 }
 
-fn digit_key(digit: &Digit) -> String {
-    let mut chars: Vec<char> = digit.iter().copied().collect();
-    chars.sort_unstable();
-    chars.into_iter().collect()
-}
+impl Io {
+    /// The wiring of a seven-segment display that fits every pattern seen on it
+    pub fn wiring(&self) -> Result<Wiring, WiringError> {
+        let observations: Vec<&str> = self
+            .input
+            .iter()
+            .chain(&self.output)
+            .map(String::as_str)
+            .collect();
+        SEVEN_SEGMENT.solve(&observations)
+    }
 
-struct Io {
-    input: Vec<Digit>,
-    output: Vec<Digit>,
+    /// Number shown by the output digits with `wiring`
+    pub fn output(&self, wiring: &Wiring) -> Result<usize, WiringError> {
+        self.output.iter().try_fold(0, |number, digit| {
+            SEVEN_SEGMENT
+                .decode(wiring, digit)
+                .map(|digit| number * 10 + digit)
+                .ok_or(WiringError::Impossible)
+        })
+    }
 }
 
 impl FromStr for Io {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data: Vec<&str> = s.splitn(2, " | ").collect();
-        let input: Vec<Digit> = data[0]
+        if let Some((column, c)) = s
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !matches!(c, 'a'..='g' | '|' | ' '))
+        {
+            return Err(ParseError::new("a segment from a to g", c).at_column(column + 1));
+        }
+
+        let (input, output) = s
+            .split_once(" | ")
+            .ok_or_else(|| ParseError::new("`<patterns> | <output>`", s))?;
+
+        let input: Vec<String> = input
             .trim()
             .split_ascii_whitespace()
-            .map(|s| s.chars().collect())
+            .map(String::from)
             .collect();
 
         if input.len() != 10 {
-            dbg!("Input len > 10");
-            return Err(());
+            return Err(ParseError::new(
+                "10 signal patterns",
+                format!("{} patterns", input.len()),
+            ));
         }
 
-        let output: Vec<Digit> = data[1]
+        let output: Vec<String> = output
             .trim()
             .split_ascii_whitespace()
-            .map(|s| s.chars().collect())
+            .map(String::from)
             .collect();
 
+        if output.len() != 4 {
+            return Err(ParseError::new(
+                "4 output digits",
+                format!("{} digits", output.len()),
+            ));
+        }
+
+        if let Some(pattern) = input.iter().chain(&output).find(|pattern| {
+            pattern
+                .char_indices()
+                .any(|(i, wire)| pattern[i + 1..].contains(wire))
+        }) {
+            return Err(ParseError::new(
+                "patterns of distinct wires",
+                pattern.as_str(),
+            ));
+        }
+
         Ok(Self { input, output })
     }
 }
 
-fn solve_part_1(data: &[Io]) -> usize {
+pub fn solve_part_1<'a>(data: impl IntoIterator<Item = &'a Io>) -> usize {
     let mut results = {
         let mut map = HashMap::new();
         for i in [1, 4, 7, 8] {
@@ -243,19 +364,43 @@ fn solve_part_1(data: &[Io]) -> usize {
     results.values().sum()
 }
 
-fn solve_part_2(data: &[Io]) -> usize {
-    data.iter()
-        .map(|io| {
-            let (_, mapping) = build_mappings(&io.input);
-            let remapped: Vec<Digit> = io.output.iter().map(|d| remap_digit(d, &mapping)).collect();
-            digits_to_num(&remapped)
-        })
-        .sum()
+pub fn solve_part_2(data: &[(Io, Wiring)]) -> Result<usize, WiringError> {
+    data.iter().map(|(io, wiring)| io.output(wiring)).sum()
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Vec<(Io, Wiring)>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    /// Lines of patterns with the single wiring that fits them
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        let data: Vec<Io> = io::read_vec(reader)?;
+        data.into_iter()
+            .enumerate()
+            .map(|(line, io)| match io.wiring() {
+                Ok(wiring) => Ok((io, wiring)),
+                Err(e) => Err(error::Error::parse_line(line + 1, &io.to_string(), e)),
+            })
+            .collect()
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part_1(input.iter().map(|(io, _)| io))
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part_2(input).expect("Wirings solved with the output digits by `Day08::parse`")
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_08::{solve_part_1, solve_part_2, Io, DIGITS};
+    use crate::day_08::{solve_part_1, solve_part_2, Day08, Font, Io, WiringError, DIGITS};
+    use crate::error::ParseError;
+    use crate::solution::Solution;
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -284,26 +429,110 @@ mod test {
             println!(
                 "{} -> {}",
                 c,
-                DIGITS.iter().filter(|s| s.contains(&c)).count()
+                DIGITS.iter().filter(|s| s.contains(c)).count()
             );
         }
     }
 
     #[test]
-    fn example_part_2() -> std::io::Result<()> {
-        let data: Vec<Io> =
-            io::read_vec_from_file(&PathBuf::from_str("./inputs/day_08_example.txt").unwrap())?;
+    fn example_part_2() -> crate::error::Result<()> {
+        let data = Day08.parse(io::open_file_read(
+            &PathBuf::from_str("./inputs/day_08_example.txt").unwrap(),
+        )?)?;
 
-        assert_eq!(solve_part_2(&data), 61229);
+        assert_eq!(solve_part_2(&data), Ok(61229));
         Ok(())
     }
 
     #[test]
-    fn part_2() -> std::io::Result<()> {
-        let data: Vec<Io> =
-            io::read_vec_from_file(&PathBuf::from_str("./inputs/day_08.txt").unwrap())?;
+    fn part_2() -> crate::error::Result<()> {
+        let data = Day08.parse(io::open_file_read(
+            &PathBuf::from_str("./inputs/day_08.txt").unwrap(),
+        )?)?;
 
-        println!("Day 8 part 2 solution: {}", solve_part_2(&data));
+        println!("Day 8 part 2 solution: {}", solve_part_2(&data).unwrap());
         Ok(())
     }
+
+    #[test]
+    fn custom_font() {
+        // Five segments, each wire is named after the next segment
+        let font = Font::new(&["ce", "a", "abc", "ab", "abcde", "abcd"]).unwrap();
+        let shift = |glyph: &str| -> String {
+            glyph
+                .chars()
+                .map(|c| (b'a' + (c as u8 - b'a' + 1) % 5) as char)
+                .collect()
+        };
+        let observed: Vec<String> = ["ce", "a", "abc", "ab", "abcde", "abcd"]
+            .iter()
+            .map(|glyph| shift(glyph))
+            .collect();
+        let observed: Vec<&str> = observed.iter().map(String::as_str).collect();
+        let wiring = font.solve(&observed).unwrap();
+        assert_eq!(wiring.segment('b'), Some('a'));
+        assert_eq!(wiring.to_string(), "a->e b->a c->b d->c e->d");
+        for (i, pattern) in observed.iter().enumerate() {
+            assert_eq!(font.decode(&wiring, pattern), Some(i));
+        }
+    }
+
+    #[test]
+    fn wiring_errors() {
+        let font = Font::new(&DIGITS).unwrap();
+        assert_eq!(
+            font.solve(&["ab", "cx"]),
+            Err(WiringError::UnknownWire('x'))
+        );
+        // Two different ones
+        assert_eq!(
+            font.solve(&["ab", "cd", "abc"]),
+            Err(WiringError::Impossible)
+        );
+        assert!(matches!(
+            font.solve(&["ab", "abc"]),
+            Err(WiringError::Ambiguous(_, _))
+        ));
+        let error = Font::new(&["ab"]).unwrap().solve(&["ab"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "both `a->a b->b` and `a->b b->a` fit the patterns"
+        );
+        assert!(Font::new(&["ab", "ba"]).is_err());
+        assert_eq!(
+            Font::new(&[]),
+            Err(ParseError::new("at least one segment", "no segments"))
+        );
+        assert!(Font::new(&["", "a"]).is_ok());
+        let wide: String = (0..65)
+            .map(|i| char::from_u32(0x100 + i).unwrap())
+            .collect();
+        assert!(Font::new(&[&wide]).is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let missing_separator = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        assert_eq!(
+            missing_separator.parse::<Io>().err(),
+            Some(ParseError::new(
+                "`<patterns> | <output>`",
+                missing_separator
+            ))
+        );
+        assert_eq!(
+            "ab cx | ab".parse::<Io>().err(),
+            Some(ParseError::new("a segment from a to g", "x").at_column(5))
+        );
+        let repeated = "ab abc abcd abcde abcdef abcdefg b c d e | aab b c d";
+        assert_eq!(
+            repeated.parse::<Io>().err(),
+            Some(ParseError::new("patterns of distinct wires", "aab"))
+        );
+        let impossible = "a b c d e f g ab ac ad | a b c d";
+        let error = Day08.parse(impossible.as_bytes()).unwrap_err();
+        assert!(error.to_string().ends_with(
+            "at line 1 `a b c d e f g ab ac ad | a b c d`: no wiring fits the patterns"
+        ));
+    }
 }
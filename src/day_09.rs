@@ -1,153 +1,169 @@
-use std::collections::HashSet;
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::{Grid, Point};
+use crate::utils::io;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
 
-static CHECKS: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-
-struct HeatMap(Vec<Vec<u8>>);
+pub struct HeatMap(Grid<u8>);
 
 impl FromStr for HeatMap {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(
-            s.lines()
-                .map(|l| {
-                    l.trim()
-                        .chars()
-                        .map(|c| c.to_string().parse().unwrap())
-                        .collect::<Vec<u8>>()
-                })
-                .collect(),
-        ))
+        Ok(Self(s.parse()?))
     }
 }
 
-struct PointSurroundsIterator<'heatmap> {
-    heatmap: &'heatmap HeatMap,
-    checks: Box<dyn Iterator<Item = &'static (isize, isize)>>,
-    row: usize,
-    column: usize,
-}
-
-impl<'heatmap> PointSurroundsIterator<'heatmap> {
-    fn new(heatmap: &'heatmap HeatMap, row: usize, column: usize) -> Self {
-        Self {
-            heatmap,
-            checks: Box::new(CHECKS.iter()),
-            row,
-            column,
-        }
+impl HeatMap {
+    fn low_points(&self) -> impl Iterator<Item = (u8, Point)> + '_ {
+        self.0.iter().filter_map(move |(point, &height)| {
+            if self.0.neighbours_4(point).all(|n| self.0[n] > height) {
+                Some((height, point))
+            } else {
+                None
+            }
+        })
     }
-}
 
-impl<'heatmap> Iterator for PointSurroundsIterator<'heatmap> {
-    type Item = u8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.checks.next() {
-                None => return None,
-                Some(&(row, column)) => {
-                    if let Some(n) = self
-                        .heatmap
-                        .get(self.row as isize + row, self.column as isize + column)
-                    {
-                        return Some(n);
+    /// Split the map into basins, the areas that 9s surround, flooding each one iteratively
+    pub fn segment(&self) -> Segmentation {
+        // `None` until flooded
+        let mut labels = self
+            .0
+            .map(|&height| (height == RIDGE).then_some(Label::Ridge));
+        let mut basins = Vec::new();
+        for start in self.0.points() {
+            if labels[start].is_some() {
+                continue;
+            }
+            let id = basins.len();
+            let mut basin = Basin {
+                low_point: start,
+                size: 0,
+                bounding_box: (start, start),
+                depth_profile: [0; RIDGE as usize],
+            };
+            labels[start] = Some(Label::Basin(id));
+            let mut stack = vec![start];
+            while let Some(point) = stack.pop() {
+                basin.add(point, self.0[point], self.0[basin.low_point]);
+                for next in self.0.neighbours_4(point) {
+                    if labels[next].is_none() {
+                        labels[next] = Some(Label::Basin(id));
+                        stack.push(next);
                     }
                 }
             }
+            basins.push(basin);
+        }
+        Segmentation {
+            labels: labels.map(|label| label.expect("Every cell flooded")),
+            basins,
         }
     }
 }
 
-impl HeatMap {
-    fn get_surrounds(&self, row: usize, column: usize) -> impl Iterator<Item = u8> + '_ {
-        PointSurroundsIterator::new(self, row, column)
-    }
+/// Height of the cells between basins
+const RIDGE: u8 = 9;
 
-    fn get(&self, row: isize, column: isize) -> Option<u8> {
-        if [
-            row < 0,
-            column < 0,
-            row as usize >= self.0.len(),
-            column as usize >= self.0[0].len(),
-        ]
-        .iter()
-        .any(|b| *b)
-        {
-            return None;
-        }
-        Some(self.0[row as usize][column as usize])
-    }
+/// What a cell of the map belongs to
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Label {
+    /// A cell of height 9, in no basin
+    Ridge,
+    /// Index of the basin in [`Segmentation::basins`]
+    Basin(usize),
+}
 
-    fn low_points(&self) -> impl Iterator<Item = (u8, (usize, usize))> + '_ {
-        self.0.iter().enumerate().flat_map(move |(row, v)| {
-            v.iter().enumerate().filter_map(move |(column, e)| {
-                let mut surrounds = self.get_surrounds(row, column);
-                if surrounds.all(|s| s > *e) {
-                    Some((*e, (row, column)))
-                } else {
-                    None
-                }
-            })
-        })
-    }
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Basin {
+    /// The lowest cell, the first one in reading order on ties
+    pub low_point: Point,
+    pub size: usize,
+    /// Top left and bottom right corners, included
+    pub bounding_box: (Point, Point),
+    /// Number of cells of each height
+    pub depth_profile: [usize; RIDGE as usize],
+}
 
-    fn basin_at_with_previous(
-        &self,
-        row: isize,
-        column: isize,
-        previous: u8,
-        visited: &mut HashSet<(isize, isize)>,
-    ) -> usize {
-        if visited.contains(&(row, column)) {
-            return 0;
-        }
-        if let Some(current) = self.get(row, column) {
-            if current > previous && current < 9 {
-                visited.insert((row, column));
-                1 + self.basin_at(row, column, visited)
-            } else {
-                0
-            }
-        } else {
-            0
+impl Basin {
+    fn add(&mut self, point: Point, height: u8, low: u8) {
+        self.size += 1;
+        self.depth_profile[height as usize] += 1;
+        if (height, point) < (low, self.low_point) {
+            self.low_point = point;
         }
+        let ((top, left), (bottom, right)) = self.bounding_box;
+        self.bounding_box = (
+            (top.min(point.0), left.min(point.1)),
+            (bottom.max(point.0), right.max(point.1)),
+        );
     }
+}
 
-    fn basin_at(&self, row: isize, column: isize, visited: &mut HashSet<(isize, isize)>) -> usize {
-        visited.insert((row, column));
-        CHECKS
-            .iter()
-            .map(|(i, j)| {
-                self.basin_at_with_previous(
-                    row + *i,
-                    column + *j,
-                    self.get(row, column).unwrap(),
-                    visited,
-                )
+/// Basins of a map and the label of each cell
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Segmentation {
+    pub labels: Grid<Label>,
+    pub basins: Vec<Basin>,
+}
+
+/// The map with `#` for ridges and a letter for each basin, letters cycle from `a` to `Z`
+impl Display for Segmentation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let letters: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+        write!(
+            f,
+            "{}",
+            self.labels.map(|label| match label {
+                Label::Basin(id) => letters[id % letters.len()],
+                Label::Ridge => '#',
             })
-            .sum()
+        )
     }
 }
 
-fn solve_part_1(heatmap: &HeatMap) -> usize {
+pub fn solve_part_1(heatmap: &HeatMap) -> usize {
     heatmap.low_points().map(|(e, _)| e as usize + 1usize).sum()
 }
 
-fn solve_part_2(heatmap: &HeatMap) -> usize {
-    let mut visited = HashSet::new();
-    let mut basins: Vec<usize> = heatmap
-        .low_points()
-        .map(|(_, (row, column))| 1 + heatmap.basin_at(row as isize, column as isize, &mut visited))
+pub fn solve_part_2(heatmap: &HeatMap) -> usize {
+    let mut sizes: Vec<usize> = heatmap
+        .segment()
+        .basins
+        .iter()
+        .map(|basin| basin.size)
         .collect();
-    basins.sort_unstable();
-    basins.iter().rev().take(3).product()
+    sizes.sort_unstable();
+    sizes.iter().rev().take(3).product()
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = HeatMap;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_object(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part_1(input)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_09::{solve_part_1, solve_part_2, HeatMap};
+    use crate::day_09::{solve_part_1, solve_part_2, Basin, HeatMap, Label};
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -186,6 +202,58 @@ mod test {
         assert_eq!(solve_part_2(&heatmap), 1134);
     }
 
+    #[test]
+    fn segmentation() {
+        let input = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
+        let heatmap: HeatMap = input.parse().unwrap();
+        let segmentation = heatmap.segment();
+        assert_eq!(
+            segmentation.to_string(),
+            "\
+aa###bbbbb
+a#ccc#b#bb
+#ccccc#d#b
+ccccc#ddd#
+#c###ddddd
+"
+        );
+        assert_eq!(segmentation.labels[(0, 2)], Label::Ridge);
+        assert_eq!(segmentation.labels[(3, 0)], Label::Basin(2));
+        assert_eq!(
+            segmentation.basins[2],
+            Basin {
+                low_point: (2, 2),
+                size: 14,
+                bounding_box: ((1, 0), (4, 5)),
+                depth_profile: [0, 0, 0, 0, 0, 1, 2, 4, 7],
+            }
+        );
+        let low_points: Vec<_> = segmentation.basins.iter().map(|b| b.low_point).collect();
+        assert_eq!(low_points, vec![(0, 1), (0, 9), (2, 2), (4, 6)]);
+
+        // Deep enough for a recursive flood to overflow the stack
+        let snake = (0..2000)
+            .map(|row| {
+                if row % 2 == 0 {
+                    "0".repeat(500)
+                } else {
+                    "9".repeat(499) + "0"
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let heatmap: HeatMap = snake.parse().unwrap();
+        let segmentation = heatmap.segment();
+        assert_eq!(segmentation.basins.len(), 1);
+        assert_eq!(segmentation.basins[0].size, 1000 * 500 + 1000);
+    }
+
     #[test]
     fn part_2() -> std::io::Result<()> {
         let heatmap: HeatMap =
@@ -1,116 +1,384 @@
-use std::collections::{HashMap, VecDeque};
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
 use std::convert::Infallible;
-use std::lazy::Lazy;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
+use std::sync::LazyLock;
 
-const CORRUPTED_SCORES: Lazy<HashMap<char, usize>> = Lazy::new(|| {
-    [(')', 3), (']', 57), ('}', 1197), ('>', 25137)]
-        .into_iter()
-        .collect()
-});
+/// A pair of delimiters and the scores of the closing one
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Delimiter {
+    pub open: char,
+    pub close: char,
+    /// Score of a line the closing delimiter corrupts
+    pub corrupted_score: usize,
+    /// Value of the closing delimiter in completion scores
+    pub completion_score: usize,
+}
 
-const INCOMPLETE_SCORES: Lazy<HashMap<char, usize>> = Lazy::new(|| {
-    [(')', 1), (']', 2), ('}', 3), ('>', 4)]
-        .into_iter()
-        .collect()
+static NAVIGATION: LazyLock<Language> = LazyLock::new(|| {
+    Language::new(vec![
+        Delimiter {
+            open: '(',
+            close: ')',
+            corrupted_score: 3,
+            completion_score: 1,
+        },
+        Delimiter {
+            open: '[',
+            close: ']',
+            corrupted_score: 57,
+            completion_score: 2,
+        },
+        Delimiter {
+            open: '{',
+            close: '}',
+            corrupted_score: 1197,
+            completion_score: 3,
+        },
+        Delimiter {
+            open: '<',
+            close: '>',
+            corrupted_score: 25137,
+            completion_score: 4,
+        },
+    ])
+    .expect("Distinct delimiters")
 });
 
-const OPPOSITES: Lazy<HashMap<char, char>> = Lazy::new(|| {
-    [
-        (')', '('),
-        (']', '['),
-        ('}', '{'),
-        ('>', '<'),
-        ('(', ')'),
-        ('[', ']'),
-        ('{', '}'),
-        ('<', '>'),
-    ]
-    .into_iter()
-    .collect()
-});
+/// Lines of nested chunks, each one between the two characters of a delimiter
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Language {
+    delimiters: Vec<Delimiter>,
+}
 
-// Navigation subsystem syntax line
-struct Nssl(String);
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SyntaxErrorKind {
+    /// A chunk closed with the wrong delimiter
+    Mismatched { expected: char },
+    /// A closing delimiter with no chunk open
+    Unopened,
+    /// A character that isn't a delimiter
+    Unknown,
+}
 
-impl FromStr for Nssl {
-    type Err = Infallible;
+/// An error at a character of a line, `column` is 1-based
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SyntaxError {
+    pub column: usize,
+    pub found: char,
+    pub kind: SyntaxErrorKind,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.to_string()))
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            SyntaxErrorKind::Mismatched { expected } => {
+                write!(f, "expected `{}`, found `{}`", expected, self.found)?
+            }
+            SyntaxErrorKind::Unopened => write!(f, "`{}` closes no chunk", self.found)?,
+            SyntaxErrorKind::Unknown => write!(f, "`{}` isn't a delimiter", self.found)?,
+        }
+        write!(f, " at column {}", self.column)
     }
 }
 
-#[derive(Debug)]
-enum NsslState {
-    Complete,
-    Incomplete(Vec<char>),
-    Corrupted(char),
+impl std::error::Error for SyntaxError {}
+
+/// Result of checking a line
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Report {
+    /// Every error, in the line order
+    pub errors: Vec<SyntaxError>,
+    /// Closing delimiters of the chunks left open, innermost first
+    pub completion: String,
 }
 
-fn incomplete_score(delimiters: &[char]) -> usize {
-    delimiters
-        .iter()
-        .fold(0, |accum, entry| accum * 5 + INCOMPLETE_SCORES[entry])
+/// A change of a line, columns are 1-based and refer to the line before any change
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Edit {
+    /// Insert before the character at `column`, or at the end if it's past the line
+    Insert {
+        column: usize,
+        c: char,
+    },
+    Delete {
+        column: usize,
+        c: char,
+    },
+    Replace {
+        column: usize,
+        from: char,
+        to: char,
+    },
 }
 
-impl NsslState {
-    fn as_score(&self) -> usize {
-        match self {
-            NsslState::Complete => 0,
-            NsslState::Incomplete(delimiters) => incomplete_score(delimiters),
-            NsslState::Corrupted(c) => CORRUPTED_SCORES[c],
+/// Fewest edits making a line valid, and the line once edited
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Repair {
+    pub edits: Vec<Edit>,
+    pub line: String,
+}
+
+/// How [`Language::repair`] handles a range of the line from its first character
+#[derive(Debug, Clone, Copy)]
+enum Choice {
+    /// Nothing to handle
+    Empty,
+    /// The first character stands alone, its partner is inserted or it's deleted
+    Alone,
+    /// The first character pairs with the one at the index, as the delimiter
+    Pair(usize, usize),
+}
+
+impl Language {
+    /// Language of at least one delimiter, delimiters share no character
+    pub fn new(delimiters: Vec<Delimiter>) -> Result<Self, ParseError> {
+        if delimiters.is_empty() {
+            return Err(ParseError::new("at least one delimiter", "no delimiters"));
+        }
+        let mut seen = Vec::new();
+        for c in delimiters.iter().flat_map(|d| [d.open, d.close]) {
+            if seen.contains(&c) {
+                return Err(ParseError::new("distinct delimiters", c));
+            }
+            seen.push(c);
         }
+        Ok(Self { delimiters })
     }
-}
 
-impl Nssl {
-    fn state(&self) -> NsslState {
-        let mut stack = VecDeque::new();
-        for c in self.0.chars() {
-            match c {
-                c @ ('(' | '[' | '{' | '<') => stack.push_back(c),
-                c @ (')' | ']' | '}' | '>') => {
-                    if let Some(initial) = stack.pop_back() {
-                        if OPPOSITES[&c] != initial {
-                            return NsslState::Corrupted(c);
-                        }
+    /// Parentheses, brackets, braces and angle brackets of the navigation subsystem
+    pub fn navigation() -> &'static Self {
+        &NAVIGATION
+    }
+
+    fn opening(&self, c: char) -> Option<&Delimiter> {
+        self.delimiters.iter().find(|d| d.open == c)
+    }
+
+    fn closing(&self, c: char) -> Option<&Delimiter> {
+        self.delimiters.iter().find(|d| d.close == c)
+    }
+
+    /// Check a whole line. A wrong closing delimiter still closes the innermost chunk, and
+    /// other characters in error are skipped.
+    pub fn check(&self, line: &str) -> Report {
+        let mut stack: Vec<&Delimiter> = Vec::new();
+        let mut errors = Vec::new();
+        for (i, c) in line.chars().enumerate() {
+            if let Some(delimiter) = self.opening(c) {
+                stack.push(delimiter);
+                continue;
+            }
+            let kind = if self.closing(c).is_some() {
+                match stack.pop() {
+                    Some(open) if open.close == c => continue,
+                    Some(open) => SyntaxErrorKind::Mismatched {
+                        expected: open.close,
+                    },
+                    None => SyntaxErrorKind::Unopened,
+                }
+            } else {
+                SyntaxErrorKind::Unknown
+            };
+            errors.push(SyntaxError {
+                column: i + 1,
+                found: c,
+                kind,
+            });
+        }
+        Report {
+            errors,
+            completion: stack.iter().rev().map(|d| d.close).collect(),
+        }
+    }
+
+    /// Score of the first closing delimiter in error, unknown characters are skipped.
+    /// `None` if no closing delimiter is.
+    pub fn corrupted_score(&self, report: &Report) -> Option<usize> {
+        report
+            .errors
+            .iter()
+            .find_map(|error| self.closing(error.found))
+            .map(|d| d.corrupted_score)
+    }
+
+    /// Score of the completion of a line with no error, `None` if it has errors or is complete
+    pub fn completion_score(&self, report: &Report) -> Option<usize> {
+        if !report.errors.is_empty() || report.completion.is_empty() {
+            return None;
+        }
+        report.completion.chars().try_fold(0, |score, c| {
+            Some(score * 5 + self.closing(c)?.completion_score)
+        })
+    }
+
+    /// Fewest substitutions and minimal change of a pair of characters into a delimiter
+    fn pair_cost(&self, open: char, close: char) -> (usize, usize) {
+        self.delimiters
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                (
+                    usize::from(d.open != open) + usize::from(d.close != close),
+                    i,
+                )
+            })
+            .min()
+            .expect("Languages have delimiters")
+    }
+
+    /// Fewest insertions, deletions and substitutions making `line` valid. Every way of
+    /// splitting the line is weighed, in O(n³) for a line of n characters.
+    pub fn repair(&self, line: &str) -> Repair {
+        let chars: Vec<char> = line.chars().collect();
+        let n = chars.len();
+        // `cost[i][j]` and `choice[i][j]` for the characters `i..j`
+        let mut cost = vec![vec![0usize; n + 1]; n + 1];
+        let mut choice = vec![vec![Choice::Empty; n + 1]; n + 1];
+        for len in 1..=n {
+            for i in 0..=n - len {
+                let j = i + len;
+                let mut best = (1 + cost[i + 1][j], Choice::Alone);
+                for k in i + 1..j {
+                    let (pair, delimiter) = self.pair_cost(chars[i], chars[k]);
+                    let total = pair + cost[i + 1][k] + cost[k + 1][j];
+                    if total < best.0 {
+                        best = (total, Choice::Pair(k, delimiter));
                     }
                 }
-                _ => unreachable!(),
+                (cost[i][j], choice[i][j]) = best;
             }
         }
-        if stack.is_empty() {
-            NsslState::Complete
-        } else {
-            NsslState::Incomplete(stack.into_iter().rev().map(|c| OPPOSITES[&c]).collect())
+
+        let mut edits = Vec::new();
+        let mut repaired = String::new();
+        self.rebuild(&chars, &choice, 0, n, &mut edits, &mut repaired);
+        Repair {
+            edits,
+            line: repaired,
         }
     }
+
+    fn rebuild(
+        &self,
+        chars: &[char],
+        choice: &[Vec<Choice>],
+        i: usize,
+        j: usize,
+        edits: &mut Vec<Edit>,
+        repaired: &mut String,
+    ) {
+        let c = chars.get(i).copied();
+        match choice[i][j] {
+            Choice::Empty => {}
+            Choice::Alone => {
+                let column = i + 1;
+                if let Some(d) = c.and_then(|c| self.opening(c)) {
+                    repaired.push(d.open);
+                    self.rebuild(chars, choice, i + 1, j, edits, repaired);
+                    // Closed right before the rest of the range
+                    repaired.push(d.close);
+                    edits.push(Edit::Insert {
+                        column: j + 1,
+                        c: d.close,
+                    });
+                } else if let Some(d) = c.and_then(|c| self.closing(c)) {
+                    edits.push(Edit::Insert { column, c: d.open });
+                    repaired.push(d.open);
+                    repaired.push(d.close);
+                    self.rebuild(chars, choice, i + 1, j, edits, repaired);
+                } else {
+                    edits.push(Edit::Delete {
+                        column,
+                        c: chars[i],
+                    });
+                    self.rebuild(chars, choice, i + 1, j, edits, repaired);
+                }
+            }
+            Choice::Pair(k, delimiter) => {
+                let d = self.delimiters[delimiter];
+                for (index, wanted) in [(i, d.open), (k, d.close)] {
+                    if chars[index] != wanted {
+                        edits.push(Edit::Replace {
+                            column: index + 1,
+                            from: chars[index],
+                            to: wanted,
+                        });
+                    }
+                }
+                repaired.push(d.open);
+                self.rebuild(chars, choice, i + 1, k, edits, repaired);
+                repaired.push(d.close);
+                self.rebuild(chars, choice, k + 1, j, edits, repaired);
+            }
+        }
+    }
+}
+
+// Navigation subsystem syntax line
+pub struct Nssl(String);
+
+impl FromStr for Nssl {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
 }
 
-fn solve_part_1(lines: &[Nssl]) -> usize {
+impl Nssl {
+    pub fn check(&self) -> Report {
+        NAVIGATION.check(&self.0)
+    }
+}
+
+pub fn solve_part_1(lines: &[Nssl]) -> usize {
     lines
         .iter()
-        .map(|nssl| nssl.state())
-        .filter(|state| matches!(state, NsslState::Corrupted(_)))
-        .map(|state| state.as_score())
+        .filter_map(|nssl| NAVIGATION.corrupted_score(&nssl.check()))
         .sum()
 }
 
-fn solve_part_2(lines: &[Nssl]) -> usize {
+pub fn solve_part_2(lines: &[Nssl]) -> usize {
     let mut res: Vec<_> = lines
         .iter()
-        .map(|nssl| nssl.state())
-        .filter(|s| matches!(s, NsslState::Incomplete(_)))
-        .map(|s| s.as_score())
+        .filter_map(|nssl| NAVIGATION.completion_score(&nssl.check()))
         .collect();
     res.sort_unstable();
-    res[res.len() / 2]
+    res.get(res.len() / 2).copied().unwrap_or(0)
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Nssl>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_vec(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part_1(input)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day_10::{solve_part_1, solve_part_2, Nssl};
+    use crate::day_10::{
+        solve_part_1, solve_part_2, Delimiter, Edit, Language, Nssl, Report, SyntaxError,
+        SyntaxErrorKind,
+    };
+    use crate::error::ParseError;
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -157,6 +425,142 @@ mod test {
         assert_eq!(solve_part_2(&lines), 288957);
     }
 
+    #[test]
+    fn every_error() {
+        let language = Language::navigation();
+        let report = language.check("{([(<{}[<>[]}>{[]{[(<()>");
+        assert_eq!(
+            report.errors[0],
+            SyntaxError {
+                column: 13,
+                found: '}',
+                kind: SyntaxErrorKind::Mismatched { expected: ']' }
+            }
+        );
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.completion, ")]}})])}");
+        assert_eq!(
+            report.errors[0].to_string(),
+            "expected `]`, found `}` at column 13"
+        );
+
+        let report = language.check(")(x]");
+        assert_eq!(
+            report.errors,
+            vec![
+                SyntaxError {
+                    column: 1,
+                    found: ')',
+                    kind: SyntaxErrorKind::Unopened
+                },
+                SyntaxError {
+                    column: 3,
+                    found: 'x',
+                    kind: SyntaxErrorKind::Unknown
+                },
+                SyntaxError {
+                    column: 4,
+                    found: ']',
+                    kind: SyntaxErrorKind::Mismatched { expected: ')' }
+                },
+            ]
+        );
+        assert_eq!(language.corrupted_score(&report), Some(3));
+        let report = language.check("x(]");
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(language.corrupted_score(&report), Some(57));
+        assert_eq!(language.corrupted_score(&language.check("(x")), None);
+
+        let report = language.check("[({(<(())[]>[[{[]{<()<>>");
+        assert_eq!(
+            report,
+            Report {
+                errors: vec![],
+                completion: "}}]])})]".to_string()
+            }
+        );
+        assert_eq!(language.completion_score(&report), Some(288957));
+        assert_eq!(language.completion_score(&language.check("()")), None);
+    }
+
+    #[test]
+    fn custom_language() {
+        let language = Language::new(vec![
+            Delimiter {
+                open: '/',
+                close: '\\',
+                corrupted_score: 7,
+                completion_score: 1,
+            },
+            Delimiter {
+                open: 'b',
+                close: 'e',
+                corrupted_score: 11,
+                completion_score: 2,
+            },
+        ])
+        .unwrap();
+        let report = language.check("/b/\\e/e");
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(language.corrupted_score(&report), Some(11));
+        assert_eq!(language.check("b/").completion, "\\e");
+        let pair = |open, close| Delimiter {
+            open,
+            close,
+            corrupted_score: 0,
+            completion_score: 0,
+        };
+        assert!(Language::new(vec![pair('(', ')'); 2]).is_err());
+        assert_eq!(
+            Language::new(vec![pair('(', ')'), pair('(', ']')]),
+            Err(ParseError::new("distinct delimiters", '('))
+        );
+        assert_eq!(
+            Language::new(vec![pair('(', ')'), pair('[', ')')]),
+            Err(ParseError::new("distinct delimiters", ')'))
+        );
+        assert_eq!(
+            Language::new(vec![]),
+            Err(ParseError::new("at least one delimiter", "no delimiters"))
+        );
+    }
+
+    #[test]
+    fn repairs() {
+        let language = Language::navigation();
+        let repair = language.repair("(]");
+        assert_eq!(
+            repair.edits,
+            vec![Edit::Replace {
+                column: 2,
+                from: ']',
+                to: ')'
+            }]
+        );
+        assert_eq!(repair.line, "()");
+
+        let repair = language.repair("[(x)");
+        assert_eq!(repair.line, "[()]");
+        assert_eq!(
+            repair.edits,
+            vec![
+                Edit::Delete { column: 3, c: 'x' },
+                Edit::Insert { column: 5, c: ']' }
+            ]
+        );
+        assert_eq!(language.repair(")").line, "()");
+        assert_eq!(language.repair("<{}>").edits, vec![]);
+        for line in [
+            "{([(<{}[<>[]}>{[]{[(<()>",
+            "<{([{{}}[<[[[<>{}]]]>[]]",
+            "))((",
+        ] {
+            let repair = language.repair(line);
+            assert!(language.check(&repair.line).errors.is_empty());
+            assert!(language.check(&repair.line).completion.is_empty());
+        }
+    }
+
     #[test]
     fn part_2() -> std::io::Result<()> {
         let lines: Vec<Nssl> =
@@ -1,110 +1,62 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::{Grid, Point};
+use crate::utils::io;
 use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::ops::AddAssign;
 use std::str::FromStr;
 
-static CHECKS: [(isize, isize); 8] = [
-    (0, 1),
-    (0, -1),
-    (1, 0),
-    (-1, 0),
-    (1, 1),
-    (-1, -1),
-    (1, -1),
-    (-1, 1),
-];
-
 #[derive(Debug, Clone)]
-struct Matrix(Vec<Vec<u8>>);
+pub struct Matrix(Grid<u8>);
 
 impl Display for Matrix {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for l in &self.0 {
-            writeln!(f, "{:?}", l)?;
-        }
-        Ok(())
+        write!(f, "{}", self.0)
     }
 }
 
 impl Matrix {
-    fn get(&self, row: isize, column: isize) -> Option<u8> {
-        if [
-            row < 0,
-            column < 0,
-            row as usize >= self.0.len(),
-            column as usize >= self.0[0].len(),
-        ]
-        .iter()
-        .any(|b| *b)
-        {
-            return None;
-        }
-        Some(self.0[row as usize][column as usize])
-    }
-
-    fn get_mut(&mut self, row: isize, column: isize) -> Option<&mut u8> {
-        if [
-            row < 0,
-            column < 0,
-            row as usize >= self.0.len(),
-            column as usize >= self.0[0].len(),
-        ]
-        .iter()
-        .any(|b| *b)
-        {
-            return None;
-        }
-        Some(&mut self.0[row as usize][column as usize])
-    }
-
     fn inc(&mut self, value: u8) {
-        self.0.iter_mut().for_each(|v| {
-            v.iter_mut().for_each(|e| {
-                *e += value;
-            })
-        })
-    }
-
-    fn flash_at(&mut self, row: isize, column: isize, inc: u8) -> usize {
-        if let Some(n) = self.get_mut(row, column) {
-            match n {
-                0 => 0,
-                i if *i > 9u8 => {
-                    *i = 0;
-                    1usize
-                        + CHECKS
-                            .iter()
-                            .map(|(r, c)| self.flash_at(row + r, column + c, 1))
-                            .sum::<usize>()
-                }
-                i => {
-                    *i += inc;
-                    0
-                }
+        self.0.values_mut().for_each(|e| *e += value)
+    }
+
+    fn flash_at(&mut self, point: Point, inc: u8) -> usize {
+        match &mut self.0[point] {
+            0 => 0,
+            i if *i > 9u8 => {
+                *i = 0;
+                1usize
+                    + self
+                        .0
+                        .neighbours_8(point)
+                        .map(|next| self.flash_at(next, 1))
+                        .sum::<usize>()
+            }
+            i => {
+                *i += inc;
+                0
             }
-        } else {
-            0
         }
     }
 
     fn flash(&mut self) -> usize {
-        let mut ret = 0;
-        for row in 0..self.0.len() as isize {
-            for column in 0..self.0[0].len() as isize {
-                ret += self.flash_at(row, column, 0);
-            }
-        }
-        ret
+        let points: Vec<Point> = self.0.points().collect();
+        points
+            .into_iter()
+            .map(|point| self.flash_at(point, 0))
+            .sum()
     }
 
     fn any_flash(&self) -> bool {
-        self.0.iter().flat_map(|v| v.iter()).any(|n| *n > 9)
+        self.0.values().any(|n| *n > 9)
     }
 
     fn flash_count_reset(&mut self) -> usize {
         let mut ret = 0;
         while self.any_flash() {
             ret += self.flash();
-            // self.reset();
         }
         ret
     }
@@ -115,61 +67,26 @@ impl Matrix {
     }
 
     fn is_zero(&self) -> bool {
-        self.0.iter().flat_map(|v| v.iter()).all(|n| *n == 0)
+        self.0.values().all(|n| *n == 0)
     }
 }
 
 impl AddAssign for Matrix {
     fn add_assign(&mut self, rhs: Self) {
-        assert!(!self.0.is_empty());
-        assert!(!rhs.0.is_empty());
-        assert_eq!(self.0.len(), rhs.0.len());
-        assert_eq!(self.0[0].len(), rhs.0[0].len());
-
-        for row in 0..self.0.len() {
-            for column in 0..self.0[0].len() {
-                self.0[row][column] += rhs.0[row][column];
-            }
-        }
-    }
-}
-
-struct SurroundsIterator<'matrix> {
-    matrix: &'matrix Matrix,
-    checks: Box<dyn Iterator<Item = &'static (isize, isize)>>,
-    row: usize,
-    column: usize,
-}
+        assert_eq!(self.0.width(), rhs.0.width());
+        assert_eq!(self.0.height(), rhs.0.height());
 
-impl<'matrix> Iterator for SurroundsIterator<'matrix> {
-    type Item = u8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.checks.next() {
-                None => return None,
-                Some(&(row, column)) => {
-                    if let Some(n) = self
-                        .matrix
-                        .get(self.row as isize + row, self.column as isize + column)
-                    {
-                        return Some(n);
-                    }
-                }
-            }
+        for (e, r) in self.0.values_mut().zip(rhs.0.values()) {
+            *e += r;
         }
     }
 }
 
 impl FromStr for Matrix {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(
-            s.lines()
-                .map(|l| l.chars().map(|c| c.to_string().parse().unwrap()).collect())
-                .collect(),
-        ))
+        Ok(Self(s.parse()?))
     }
 }
 
@@ -177,11 +94,11 @@ fn simulate(mut matrix: Matrix, steps: usize) -> usize {
     (0..steps).map(|_| matrix.step()).sum()
 }
 
-fn solve_part_1(matrix: Matrix) -> usize {
+pub fn solve_part_1(matrix: Matrix) -> usize {
     simulate(matrix, 100)
 }
 
-fn solve_part_2(mut matrix: Matrix) -> usize {
+pub fn solve_part_2(mut matrix: Matrix) -> usize {
     for i in 1.. {
         matrix.step();
         if matrix.is_zero() {
@@ -191,6 +108,26 @@ fn solve_part_2(mut matrix: Matrix) -> usize {
     unreachable!()
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Matrix;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_object(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part_1(input.clone())
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part_2(input.clone())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::day_11::{solve_part_1, solve_part_2, Matrix};
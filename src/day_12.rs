@@ -1,19 +1,33 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
 use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
 use std::str::FromStr;
 
-#[derive(Debug)]
-struct Pathways(HashMap<String, HashSet<String>>);
+#[derive(Debug, Clone)]
+pub struct Pathways(HashMap<String, HashSet<String>>);
 
 impl FromStr for Pathways {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut map: HashMap<String, HashSet<String>> = HashMap::new();
-        for l in s.lines() {
-            let (init, end): (String, String) = sscanf::scanf!(l, "{}-{}", String, String).unwrap();
+        for (line, l) in s.lines().enumerate() {
+            let (init, end): (String, String) = sscanf::scanf!(l, "{}-{}", String, String)
+                .ok_or_else(|| ParseError::new("`<cave>-<cave>`", l).at_line(line + 1))?;
             map.entry(init.clone()).or_default().insert(end.clone());
             map.entry(end).or_default().insert(init);
         }
+        for cave in ["start", "end"] {
+            if !map.contains_key(cave) {
+                return Err(ParseError::new(
+                    format!("the `{}` cave", cave),
+                    format!("no `{}` cave", cave),
+                ));
+            }
+        }
         map.remove("end");
         Ok(Self(map))
     }
@@ -89,7 +103,7 @@ impl Pathways {
     }
 }
 
-fn solve_part_1(paths: Pathways) -> usize {
+pub fn solve_part_1(paths: Pathways) -> usize {
     let generated = paths.generate_paths(0);
     generated
         .iter()
@@ -97,7 +111,7 @@ fn solve_part_1(paths: Pathways) -> usize {
         .count()
 }
 
-fn solve_part_2(paths: Pathways) -> usize {
+pub fn solve_part_2(paths: Pathways) -> usize {
     let generated = paths.generate_paths(2);
     generated
         .iter()
@@ -105,9 +119,30 @@ fn solve_part_2(paths: Pathways) -> usize {
         .count()
 }
 
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Pathways;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_object(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve_part_1(input.clone())
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve_part_2(input.clone())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::day_12::{solve_part_1, solve_part_2, Pathways};
+    use crate::error::ParseError;
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -155,4 +190,16 @@ b-end";
         println!("Day 12 part 2 solution: {}", solve_part_2(pathways));
         Ok(())
     }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "a-b".parse::<Pathways>().unwrap_err(),
+            ParseError::new("the `start` cave", "no `start` cave")
+        );
+        assert_eq!(
+            "start-a\na-b".parse::<Pathways>().unwrap_err(),
+            ParseError::new("the `end` cave", "no `end` cave")
+        );
+    }
 }
@@ -1,3 +1,6 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter, Write};
 use std::io::BufRead;
@@ -39,13 +42,18 @@ impl Position {
     }
 }
 
-struct Matrix(HashSet<Position>);
+#[derive(Debug, Clone)]
+pub struct Matrix(HashSet<Position>);
 
 impl Matrix {
     fn fold_by(&self, fold: Fold) -> Self {
         Self(self.0.iter().map(|p| p.fold(fold)).collect())
     }
 
+    pub fn dots(&self) -> usize {
+        self.0.len()
+    }
+
     fn size(&self) -> (usize, usize) {
         (
             self.0.iter().map(|p| p.y).max().unwrap(),
@@ -59,12 +67,8 @@ impl Display for Matrix {
         let (rows, columns) = self.size();
         for row in 0..=rows {
             for column in 0..=columns {
-                f.write_char(
-                    self.0
-                        .contains(&Position { x: column, y: row })
-                        .then_some('0')
-                        .unwrap_or(' '),
-                )?;
+                let dot = self.0.contains(&Position { x: column, y: row });
+                f.write_char(if dot { '0' } else { ' ' })?;
             }
             f.write_char('\n')?;
         }
@@ -73,64 +77,98 @@ impl Display for Matrix {
 }
 
 impl FromStr for Matrix {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(
-            s.lines()
-                .filter_map(|l| {
-                    sscanf::scanf!(l.trim(), "{},{}", usize, usize)
-                        .map(|(x, y): (usize, usize)| Position { x, y })
-                })
-                .collect(),
-        ))
+        let dots = s
+            .lines()
+            .enumerate()
+            .map(|(line, l)| {
+                sscanf::scanf!(l.trim(), "{},{}", usize, usize)
+                    .map(|(x, y): (usize, usize)| Position { x, y })
+                    .ok_or_else(|| ParseError::new("`x,y`", l).at_line(line + 1))
+            })
+            .collect::<Result<HashSet<_>, _>>()?;
+        if dots.is_empty() {
+            return Err(ParseError::new("at least one dot", "no dots"));
+        }
+        Ok(Self(dots))
     }
 }
 
 #[derive(Clone, Copy, Debug)]
-enum Fold {
+pub enum Fold {
     X(usize),
     Y(usize),
 }
 
 impl FromStr for Fold {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (axis, value): (String, usize) =
-            sscanf::scanf!(s, "fold along {}={}", String, usize).unwrap();
+            sscanf::scanf!(s, "fold along {}={}", String, usize)
+                .ok_or_else(|| ParseError::new("`fold along <axis>=<value>`", s))?;
         match axis.as_str() {
             "x" => Ok(Self::X(value)),
             "y" => Ok(Self::Y(value)),
-            _ => Err(()),
+            _ => Err(ParseError::new("x or y axis", axis)),
         }
     }
 }
 
-fn parse_input(mut reader: impl BufRead) -> (Matrix, Vec<Fold>) {
+pub fn parse_input(mut reader: impl BufRead) -> error::Result<(Matrix, Vec<Fold>)> {
     let mut buff = String::new();
-    reader.read_to_string(&mut buff).unwrap();
-    let mut parts = buff.splitn(2, "\r\n\r\n");
-    (
-        parts.next().unwrap().parse().unwrap(),
-        parts
-            .next()
-            .unwrap()
-            .lines()
-            .map(|l| l.parse().unwrap())
-            .collect(),
-    )
+    reader.read_to_string(&mut buff)?;
+    let buff = buff.replace("\r\n", "\n");
+    let (dots, folds) = buff.split_once("\n\n").ok_or_else(|| {
+        ParseError::new(
+            "dots and folds separated by an empty line",
+            "a single block",
+        )
+    })?;
+    let first_fold_line = dots.lines().count() + 2;
+    let folds = folds
+        .lines()
+        .enumerate()
+        .map(|(line, l)| {
+            l.parse()
+                .map_err(|e: ParseError| e.at_line(first_fold_line + line))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((dots.parse()?, folds))
 }
 
-fn solve(matrix: Matrix, folds: Vec<Fold>) -> Matrix {
+pub fn solve(matrix: Matrix, folds: Vec<Fold>) -> Matrix {
     folds
         .into_iter()
         .fold(matrix, |matrix, f| matrix.fold_by(f))
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = (Matrix, Vec<Fold>);
+    type PartOne = usize;
+    type PartTwo = Matrix;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        parse_input(reader)
+    }
+
+    fn part_one(&self, (matrix, folds): &Self::Input) -> Self::PartOne {
+        solve(matrix.clone(), folds.iter().copied().take(1).collect()).dots()
+    }
+
+    fn part_two(&self, (matrix, folds): &Self::Input) -> Self::PartTwo {
+        solve(matrix.clone(), folds.clone())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::day_13::{parse_input, solve};
+    use crate::day_13::{parse_input, solve, Fold};
+    use crate::error::ParseError;
     use crate::utils::io;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -139,7 +177,7 @@ mod test {
     fn parse_example_input() -> std::io::Result<()> {
         let reader =
             io::open_file_read(&PathBuf::from_str("./inputs/day_13_example.txt").unwrap())?;
-        let (matrix, folds) = parse_input(reader);
+        let (matrix, folds) = parse_input(reader)?;
         assert_eq!(matrix.0.len(), 18);
         assert_eq!(folds.len(), 2);
         Ok(())
@@ -149,7 +187,7 @@ mod test {
     fn example_part_1() -> std::io::Result<()> {
         let reader =
             io::open_file_read(&PathBuf::from_str("./inputs/day_13_example.txt").unwrap())?;
-        let (matrix, folds) = parse_input(reader);
+        let (matrix, folds) = parse_input(reader)?;
         assert_eq!(solve(matrix, folds).0.len(), 16);
         Ok(())
     }
@@ -157,7 +195,7 @@ mod test {
     #[test]
     fn part_1() -> std::io::Result<()> {
         let reader = io::open_file_read(&PathBuf::from_str("./inputs/day_13.txt").unwrap())?;
-        let (matrix, folds) = parse_input(reader);
+        let (matrix, folds) = parse_input(reader)?;
         println!(
             "Day 13 part 1 solution: {}",
             solve(matrix, folds.iter().copied().take(1).collect::<Vec<_>>())
@@ -170,8 +208,24 @@ mod test {
     #[test]
     fn part_2() -> std::io::Result<()> {
         let reader = io::open_file_read(&PathBuf::from_str("./inputs/day_13.txt").unwrap())?;
-        let (matrix, folds) = parse_input(reader);
+        let (matrix, folds) = parse_input(reader)?;
         println!("Day 13 part 1 solution: \n{}", solve(matrix, folds));
         Ok(())
     }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "fold along z=3".parse::<Fold>().unwrap_err(),
+            ParseError::new("x or y axis", "z")
+        );
+        let error = parse_input("6,10\n0,14\n\nfold along y=7\nfold x=5".as_bytes()).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("expected `fold along <axis>=<value>`, found `fold x=5` at line 5"));
+        let error = parse_input("\n\nfold along y=7".as_bytes()).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("expected at least one dot, found `no dots`"));
+    }
 }
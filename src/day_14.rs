@@ -1,15 +1,29 @@
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
 use std::collections::HashMap;
 use std::io::BufRead;
 use std::str::FromStr;
 
 #[derive(Debug, Clone)]
-struct Polymer(Vec<char>);
+pub struct Polymer(Vec<char>);
 
 impl FromStr for Polymer {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.chars().collect()))
+        if s.is_empty() {
+            return Err(ParseError::new("a polymer template", s));
+        }
+        s.chars()
+            .enumerate()
+            .map(|(column, c)| {
+                c.is_ascii_alphabetic()
+                    .then_some(c)
+                    .ok_or_else(|| ParseError::new("an element", c).at_column(column + 1))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
     }
 }
 
@@ -19,13 +33,14 @@ struct Rule {
     insertion: char,
 }
 
-type Rules = HashMap<(char, char), char>;
+pub type Rules = HashMap<(char, char), char>;
 
 impl FromStr for Rule {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (char1, char2, insertion) = sscanf::scanf!(s, "{}{} -> {}", char, char, char).unwrap();
+        let (char1, char2, insertion) = sscanf::scanf!(s, "{}{} -> {}", char, char, char)
+            .ok_or_else(|| ParseError::new("`<element><element> -> <element>`", s))?;
         Ok(Self {
             matching: (char1, char2),
             insertion,
@@ -67,33 +82,42 @@ impl Polymer {
     }
 }
 
-fn parse_input(mut reader: impl BufRead) -> (Polymer, Rules) {
+pub fn parse_input(mut reader: impl BufRead) -> error::Result<(Polymer, Rules)> {
     let mut buff = String::new();
-    reader.read_to_string(&mut buff).unwrap();
-    let mut parts = buff.splitn(2, "\n\n");
-    (
-        parts.next().unwrap().parse().unwrap(),
-        parts
-            .next()
-            .unwrap()
-            .lines()
-            .map(|l| l.parse::<Rule>().unwrap())
-            .map(|r| (r.matching, r.insertion))
-            .collect(),
-    )
+    reader.read_to_string(&mut buff)?;
+    let buff = buff.replace("\r\n", "\n");
+    let (template, rules) = buff.split_once("\n\n").ok_or_else(|| {
+        ParseError::new(
+            "template and rules separated by an empty line",
+            "a single block",
+        )
+    })?;
+    let rules = rules
+        .lines()
+        .enumerate()
+        .map(|(line, l)| {
+            l.parse::<Rule>()
+                .map(|r| (r.matching, r.insertion))
+                .map_err(|e| e.at_line(line + 3))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((
+        template.parse().map_err(|e: ParseError| e.at_line(1))?,
+        rules,
+    ))
 }
 
 fn solve(polymer: Polymer, rules: &Rules, iters: usize) -> Polymer {
     (0..iters).fold(polymer, |polymer, _| polymer.apply_rules(rules))
 }
 
-fn solve_part_1(polymer: Polymer, rules: &Rules) -> usize {
+pub fn solve_part_1(polymer: Polymer, rules: &Rules) -> usize {
     let result = solve(polymer, rules, 10);
     let (min, max) = result.min_max();
     max - min
 }
 
-fn solve_2(polymer: Polymer, rules: &Rules, iterations: usize) -> usize {
+pub fn solve_2(polymer: Polymer, rules: &Rules, iterations: usize) -> usize {
     let mut results: HashMap<(char, char), usize> = HashMap::new();
 
     for pair in polymer.0.windows(2) {
@@ -144,6 +168,26 @@ fn solve_2(polymer: Polymer, rules: &Rules, iterations: usize) -> usize {
     max - min
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = (Polymer, Rules);
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        parse_input(reader)
+    }
+
+    fn part_one(&self, (polymer, rules): &Self::Input) -> Self::PartOne {
+        solve_part_1(polymer.clone(), rules)
+    }
+
+    fn part_two(&self, (polymer, rules): &Self::Input) -> Self::PartTwo {
+        solve_2(polymer.clone(), rules, 40)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::day_14::{parse_input, solve_2, solve_part_1};
@@ -155,7 +199,7 @@ mod test {
     fn parse_example() -> std::io::Result<()> {
         let reader =
             io::open_file_read(&PathBuf::from_str("./inputs/day_14_example.txt").unwrap())?;
-        let (polymer, rules) = parse_input(reader);
+        let (polymer, rules) = parse_input(reader)?;
         assert_eq!(polymer.0.len(), 4);
         assert_eq!(rules.len(), 16);
         Ok(())
@@ -165,7 +209,7 @@ mod test {
     fn example_part_1() -> std::io::Result<()> {
         let reader =
             io::open_file_read(&PathBuf::from_str("./inputs/day_14_example.txt").unwrap())?;
-        let (polymer, rules) = parse_input(reader);
+        let (polymer, rules) = parse_input(reader)?;
         let res = solve_part_1(polymer, &rules);
         assert_eq!(res, 1588);
         Ok(())
@@ -174,7 +218,7 @@ mod test {
     #[test]
     fn part_1() -> std::io::Result<()> {
         let reader = io::open_file_read(&PathBuf::from_str("./inputs/day_14.txt").unwrap())?;
-        let (polymer, rules) = parse_input(reader);
+        let (polymer, rules) = parse_input(reader)?;
         println!("Day 14 part 1 solution: {}", solve_part_1(polymer, &rules));
         Ok(())
     }
@@ -183,7 +227,7 @@ mod test {
     fn example_part_2() -> std::io::Result<()> {
         let reader =
             io::open_file_read(&PathBuf::from_str("./inputs/day_14_example.txt").unwrap())?;
-        let (polymer, rules) = parse_input(reader);
+        let (polymer, rules) = parse_input(reader)?;
         assert_eq!(
             solve_part_1(polymer.clone(), &rules),
             solve_2(polymer, &rules, 10)
@@ -194,8 +238,18 @@ mod test {
     #[test]
     fn part_2() -> std::io::Result<()> {
         let reader = io::open_file_read(&PathBuf::from_str("./inputs/day_14.txt").unwrap())?;
-        let (polymer, rules) = parse_input(reader);
+        let (polymer, rules) = parse_input(reader)?;
         println!("Day 14 part 2 solution: {}", solve_2(polymer, &rules, 40));
         Ok(())
     }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse_input("NN1B\n\nCH -> B".as_bytes()).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("expected an element, found `1` at line 1, column 3"));
+        let error = parse_input("NNCB\n\nCH -> B\nHH - N".as_bytes()).unwrap_err();
+        assert!(error.to_string().ends_with("found `HH - N` at line 4"));
+    }
 }
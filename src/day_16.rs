@@ -1,81 +1,194 @@
-use std::any::TypeId;
-use std::collections::HashMap;
-use std::iter::Peekable;
-use std::lazy::Lazy;
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::bits::{BitReader, BitWriter};
+use crate::utils::io;
+use crate::utils::num::Natural;
+use num_bigint::BigUint;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
 use std::str::FromStr;
 
-const HEXMAP: Lazy<HashMap<char, &str>> = Lazy::new(|| {
-    [
-        ('0', "0000"),
-        ('1', "0001"),
-        ('2', "0010"),
-        ('3', "0011"),
-        ('4', "0100"),
-        ('5', "0101"),
-        ('6', "0110"),
-        ('7', "0111"),
-        ('8', "1000"),
-        ('9', "1001"),
-        ('A', "1010"),
-        ('B', "1011"),
-        ('C', "1100"),
-        ('D', "1101"),
-        ('E', "1110"),
-        ('F', "1111"),
-    ]
-    .into_iter()
-    .collect()
-});
-
 const LITERAL_ID: u8 = 4;
+/// Deepest nesting of operators decoded, so hostile transmissions can't exhaust the stack
+pub const MAX_DEPTH: usize = 256;
 
-struct BitsInput(Vec<char>);
+/// A transmission packed into bytes, two hexadecimal digits per byte
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BitsInput {
+    bytes: Vec<u8>,
+    len: usize,
+}
 
 impl FromStr for BitsInput {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.chars().flat_map(|c| HEXMAP[&c].chars()).collect()))
+        let digits = s
+            .trim()
+            .chars()
+            .map(|c| {
+                c.to_digit(16)
+                    .map(|d| d as u8)
+                    .ok_or_else(|| ParseError::new("a hexadecimal digit", c))
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        Ok(Self {
+            bytes: digits
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+                .collect(),
+            len: digits.len() * 4,
+        })
     }
 }
 
-struct BitsStream(Peekable<Box<dyn Iterator<Item = char>>>);
-
 impl BitsInput {
-    fn into_bits_stream(self) -> BitsStream {
-        let iter: Box<dyn Iterator<Item = char>> = Box::new(self.0.into_iter());
-        BitsStream(iter.peekable())
+    pub fn reader(&self) -> BitReader<'_> {
+        BitReader::new(&self.bytes, self.len)
     }
 }
 
-impl BitsStream {
-    fn take(&mut self, n: usize) -> Option<String> {
-        let mut res = Vec::new();
-        for _ in 0..n {
-            res.push(self.0.next()?);
+/// Whole bytes written by `writer`, the last one padded with zeros
+impl From<BitWriter> for BitsInput {
+    fn from(writer: BitWriter) -> Self {
+        let bytes = writer.into_bytes();
+        Self {
+            len: bytes.len() * 8,
+            bytes,
         }
-        Some(res.into_iter().collect())
     }
+}
 
-    fn discard(&mut self, n: usize) {
-        for _ in 0..n {
-            self.0.next().expect("Discarding empty stream");
+/// Uppercase hexadecimal, as transmissions are written
+impl Display for BitsInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in &self.bytes[..self.len / 8] {
+            write!(f, "{:02X}", byte)?;
+        }
+        if !self.len.is_multiple_of(8) {
+            write!(f, "{:X}", self.bytes[self.len / 8] >> 4)?;
         }
+        Ok(())
     }
+}
+
+pub trait Decode {
+    type Output;
+    fn decode(stream: &mut BitReader<'_>) -> Result<Self::Output, DecodeError>;
+}
+
+/// Why a transmission can't be decoded
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DecodeErrorKind {
+    /// The bits ran out in the middle of `field`
+    Truncated { field: &'static str },
+    /// Minimum and maximum need a subpackage, comparisons exactly two
+    Operands { id: u8, count: usize },
+    /// Operators nested deeper than [`MAX_DEPTH`]
+    TooDeep,
+    /// Bits other than zero padding after the outermost package
+    TrailingBits,
+}
+
+/// A malformed transmission, `offset` is the bit where the problem starts
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub kind: DecodeErrorKind,
+}
 
-    fn from_chars(iter: Vec<char>) -> Self {
-        let iter: Box<dyn Iterator<Item = char>> = Box::new(iter.into_iter());
-        Self(iter.peekable())
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            DecodeErrorKind::Truncated { field } => {
+                write!(f, "transmission ends in the {}", field)?
+            }
+            DecodeErrorKind::Operands { id, count } => {
+                write!(f, "operator {} can't take {} subpackages", id, count)?
+            }
+            DecodeErrorKind::TooDeep => {
+                write!(f, "packages nested deeper than {} levels", MAX_DEPTH)?
+            }
+            DecodeErrorKind::TrailingBits => write!(f, "bits left after the outermost package")?,
+        }
+        write!(f, " at bit {}", self.offset)
     }
 }
 
-trait Decode {
-    type Output;
-    fn decode(stream: &mut BitsStream) -> Option<Self::Output>;
+impl std::error::Error for DecodeError {}
+
+/// Read a `bits` wide `field`, which must be complete
+fn read_field(
+    stream: &mut BitReader<'_>,
+    field: &'static str,
+    bits: usize,
+) -> Result<u64, DecodeError> {
+    stream.read(bits).ok_or(DecodeError {
+        offset: stream.position(),
+        kind: DecodeErrorKind::Truncated { field },
+    })
+}
+
+/// Whether operator `id` is defined for `count` subpackages
+fn takes_operands(id: u8, count: usize) -> bool {
+    match id {
+        0 | 1 => true,
+        2 | 3 => count > 0,
+        5..=7 => count == 2,
+        _ => false,
+    }
+}
+
+/// A package that can't be written in the BITS format
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EncodeError {
+    /// A field value too large for its width
+    TooWide {
+        field: &'static str,
+        value: usize,
+        bits: usize,
+    },
+    /// A type id that would be read back as another kind of package,
+    /// such as an operator with the literal id
+    TypeMismatch { id: u8, package: &'static str },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::TooWide { field, value, bits } => {
+                write!(f, "{} {} doesn't fit in {} bits", field, value, bits)
+            }
+            EncodeError::TypeMismatch { id, package } => {
+                write!(f, "type id {} doesn't fit a {} package", id, package)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Counterpart of [`Decode`], writes the bits [`Decode`] reads back
+pub trait Encode {
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError>;
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum PackageType {
+fn write_field(
+    writer: &mut BitWriter,
+    field: &'static str,
+    value: usize,
+    bits: usize,
+) -> Result<(), EncodeError> {
+    if bits < usize::BITS as usize && value >> bits != 0 {
+        return Err(EncodeError::TooWide { field, value, bits });
+    }
+    writer.write(value as u64, bits);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PackageType {
     Literal,
     Operator(u8),
 }
@@ -87,128 +200,262 @@ impl PackageType {
             id => Self::Operator(id),
         }
     }
+
+    fn id(&self) -> u8 {
+        match self {
+            PackageType::Literal => LITERAL_ID,
+            PackageType::Operator(id) => *id,
+        }
+    }
+
+    /// What the type id stands for, `None` for ids evaluation doesn't know
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self.id() {
+            0 => "+",
+            1 => "*",
+            2 => "min",
+            3 => "max",
+            LITERAL_ID => "literal",
+            5 => ">",
+            6 => "<",
+            7 => "=",
+            _ => return None,
+        })
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Header {
+/// The name of the type, or its id for unknown ones
+impl Display for PackageType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "type {}", self.id()),
+        }
+    }
+}
+
+/// How an operator delimits its subpackages
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LengthType {
+    /// Length type 0, the subpackages span a 15 bits number of bits
+    TotalBits,
+    /// Length type 1, the subpackages are counted with 11 bits
+    Count,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Header {
     version: u8,
     package_type: PackageType,
 }
 
-#[derive(Debug)]
-struct LiteralPackage {
-    number: usize,
-}
+impl Header {
+    pub fn new(version: u8, package_type: PackageType) -> Self {
+        Self {
+            version,
+            package_type,
+        }
+    }
 
-#[derive(Debug)]
-struct OperatorPackage {
-    subpackages: Packages,
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn package_type(&self) -> PackageType {
+        self.package_type
+    }
 }
 
-#[derive(Debug)]
-enum Package {
-    Operator((Header, OperatorPackage)),
-    Literal((Header, LiteralPackage)),
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LiteralPackage {
+    number: BigUint,
 }
 
-impl Package {
-    fn version_sum(&self) -> usize {
-        match self {
-            Package::Operator((header, other)) => {
-                header.version as usize + other.subpackages.version_sum() as usize
-            }
-            Package::Literal((header, _)) => header.version as usize,
-        }
+impl LiteralPackage {
+    pub fn new(number: BigUint) -> Self {
+        Self { number }
     }
 
-    fn sum(&self) -> usize {
-        match self {
-            Package::Operator((_, operator)) => operator.subpackages.sum(),
-            Package::Literal((_, literal)) => literal.number,
-        }
+    pub fn number(&self) -> &BigUint {
+        &self.number
     }
+}
 
-    fn mul(&self) -> usize {
-        match self {
-            Package::Operator((_, operator)) => operator.subpackages.mul(),
-            Package::Literal((_, literal)) => literal.number,
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OperatorPackage {
+    length_type: LengthType,
+    subpackages: Packages,
+}
+
+impl OperatorPackage {
+    /// Counts the subpackages unless there are too many for 11 bits
+    pub fn new(subpackages: Vec<Package>) -> Self {
+        let length_type = if subpackages.len() < 1 << 11 {
+            LengthType::Count
+        } else {
+            LengthType::TotalBits
+        };
+        Self {
+            length_type,
+            subpackages: Packages(subpackages),
         }
     }
 
-    fn min(&self) -> usize {
-        match self {
-            Package::Operator((_, operator)) => operator.subpackages.min(),
-            Package::Literal((_, literal)) => literal.number,
+    /// Delimit the subpackages with `length_type`, whether they fit in it is only checked
+    /// when encoding
+    pub fn with_length_type(self, length_type: LengthType) -> Self {
+        Self {
+            length_type,
+            ..self
         }
     }
 
-    fn max(&self) -> usize {
-        match self {
-            Package::Operator((_, operator)) => operator.subpackages.max(),
-            Package::Literal((_, literal)) => literal.number,
-        }
+    pub fn length_type(&self) -> LengthType {
+        self.length_type
     }
 
-    fn cond(&self, op: impl FnOnce(usize, usize) -> usize) -> usize {
+    pub fn subpackages(&self) -> &Packages {
+        &self.subpackages
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Package {
+    Operator((Header, OperatorPackage)),
+    Literal((Header, LiteralPackage)),
+}
+
+impl Package {
+    pub fn version_sum(&self) -> usize {
         match self {
-            Package::Operator((_, operator)) => {
-                if let (a, b) = (
-                    operator.subpackages.0[0].compute(),
-                    operator.subpackages.0[1].compute(),
-                ) {
-                    op(a, b)
-                } else {
-                    unreachable!()
-                }
+            Package::Operator((header, other)) => {
+                header.version as usize + other.subpackages.version_sum()
             }
-            Package::Literal(_) => unreachable!(),
+            Package::Literal((header, _)) => header.version as usize,
         }
     }
 
-    fn greater(&self) -> usize {
-        self.cond(|a, b| (a > b).then_some(1).unwrap_or(0))
+    /// Value of the packet, overflows of `N` and invalid operators are reported with the packet
+    /// they happen in
+    pub fn evaluate<N: Natural>(&self) -> Result<N, EvalError> {
+        self.evaluate_at(&mut Vec::new())
     }
 
-    fn less(&self) -> usize {
-        self.cond(|a, b| (a < b).then_some(1).unwrap_or(0))
+    /// Value of the packet with overflow checked `usize` arithmetic
+    pub fn compute(&self) -> Result<usize, EvalError> {
+        self.evaluate()
     }
 
-    fn equal(&self) -> usize {
-        self.cond(|a, b| (a == b).then_some(1).unwrap_or(0))
-    }
-
-    fn compute(&self) -> usize {
+    fn evaluate_at<N: Natural>(&self, path: &mut Vec<usize>) -> Result<N, EvalError> {
+        let overflow = |path: &Vec<usize>, operation| EvalError::Overflow {
+            path: path.clone(),
+            offset: None,
+            operation,
+        };
         match self {
-            Package::Operator((header, _)) => self.compute_with_type(&header.package_type),
-            Package::Literal((_, LiteralPackage { number })) => *number,
+            Package::Literal((_, literal)) => {
+                N::from_big(&literal.number).ok_or_else(|| overflow(path, "literal"))
+            }
+            Package::Operator((header, operator)) => {
+                let id = header.package_type.id();
+                let count = operator.subpackages.0.len();
+                if !takes_operands(id, count) {
+                    return Err(EvalError::Operands {
+                        path: path.clone(),
+                        id,
+                        count,
+                    });
+                }
+                let mut values = Vec::with_capacity(count);
+                for (i, package) in operator.subpackages.0.iter().enumerate() {
+                    path.push(i);
+                    values.push(package.evaluate_at(path)?);
+                    path.pop();
+                }
+                let mut values = values.into_iter();
+                match id {
+                    0 => values
+                        .try_fold(N::zero(), |sum, value| sum.checked_add(&value))
+                        .ok_or_else(|| overflow(path, "sum")),
+                    1 => values
+                        .try_fold(N::one(), |product, value| product.checked_mul(&value))
+                        .ok_or_else(|| overflow(path, "product")),
+                    // Operand counts are checked above, hence the defaults never show up
+                    2 => Ok(values.min().unwrap_or_else(N::zero)),
+                    3 => Ok(values.max().unwrap_or_else(N::zero)),
+                    _ => {
+                        let operands: Vec<N> = values.collect();
+                        let (a, b) = (&operands[0], &operands[1]);
+                        let holds = match id {
+                            5 => a > b,
+                            6 => a < b,
+                            _ => a == b,
+                        };
+                        Ok(if holds { N::one() } else { N::zero() })
+                    }
+                }
+            }
         }
     }
+}
 
-    fn compute_with_type(&self, package_type: &PackageType) -> usize {
-        match package_type {
-            PackageType::Literal => unreachable!(),
-            PackageType::Operator(0) => self.sum(),
-            PackageType::Operator(1) => self.mul(),
-            PackageType::Operator(2) => self.min(),
-            PackageType::Operator(3) => self.max(),
-            PackageType::Operator(5) => self.greater(),
-            PackageType::Operator(6) => self.less(),
-            PackageType::Operator(7) => self.equal(),
-            _ => unreachable!(),
+/// A packet that can't be evaluated.
+/// `path` leads to the packet from the outermost one, as indexes of subpackages.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EvalError {
+    /// A value too large for the numbers the packet is evaluated with.
+    /// `offset` is the bit the packet starts at when it's evaluated straight from its bits.
+    Overflow {
+        path: Vec<usize>,
+        offset: Option<usize>,
+        operation: &'static str,
+    },
+    /// An operator undefined for its type id or its number of subpackages.
+    /// Decoded packets are always valid, only built ones can be wrong.
+    Operands {
+        path: Vec<usize>,
+        id: u8,
+        count: usize,
+    },
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (path, offset) = match self {
+            EvalError::Overflow {
+                path,
+                offset,
+                operation,
+            } => {
+                write!(f, "{} overflows", operation)?;
+                (path, *offset)
+            }
+            EvalError::Operands { path, id, count } => {
+                write!(f, "operator {} can't take {} subpackages", id, count)?;
+                (path, None)
+            }
+        };
+        write!(f, " in packet root")?;
+        for i in path {
+            write!(f, ".{}", i)?;
+        }
+        match offset {
+            Some(offset) => write!(f, " at bit {}", offset),
+            None => Ok(()),
         }
     }
 }
 
+impl std::error::Error for EvalError {}
+
 impl Decode for Header {
     type Output = Header;
 
-    fn decode(stream: &mut BitsStream) -> Option<Self::Output> {
-        let version = u8::from_str_radix(&stream.take(3)?, 2).expect("A parseable version");
-        let package_type = PackageType::from_u8(
-            u8::from_str_radix(&stream.take(3)?, 2).expect("A parseable package type"),
-        );
+    fn decode(stream: &mut BitReader<'_>) -> Result<Self::Output, DecodeError> {
+        let version = read_field(stream, "version", 3)? as u8;
+        let package_type = PackageType::from_u8(read_field(stream, "type id", 3)? as u8);
 
-        Some(Self {
+        Ok(Self {
             version,
             package_type,
         })
@@ -218,124 +465,961 @@ impl Decode for Header {
 impl Decode for LiteralPackage {
     type Output = LiteralPackage;
 
-    fn decode(stream: &mut BitsStream) -> Option<Self::Output> {
-        let mut bits: Vec<String> = Vec::new();
+    fn decode(stream: &mut BitReader<'_>) -> Result<Self::Output, DecodeError> {
+        let mut groups = Vec::new();
         loop {
-            let next = stream.take(5)?;
-            let breaking = next.starts_with('0');
-            bits.push(next.chars().skip(1).collect());
-            if breaking {
+            // A continuation bit then a hexadecimal digit
+            let group = read_field(stream, "literal group", 5)? as u8;
+            groups.push(group & 0xF);
+            if group >> 4 == 0 {
                 break;
             }
         }
-        let number_bits: String = bits.join("");
-        let number = usize::from_str_radix(&number_bits, 2).expect("Parseable number");
-        Some(Self { number })
+        Ok(Self {
+            number: BigUint::from_radix_be(&groups, 16).expect("Groups are hexadecimal digits"),
+        })
     }
 }
 
 impl OperatorPackage {
-    fn decode_length(stream: &mut BitsStream) -> Option<Self> {
-        let length = usize::from_str_radix(&stream.take(15)?, 2).expect("Parseable number");
-        let chars = stream.take(length)?.chars().collect();
-        let mut substream: BitsStream = BitsStream::from_chars(chars);
-        Some(Self {
-            subpackages: Packages::decode(&mut substream)?,
+    fn decode_length(stream: &mut BitReader<'_>, depth: usize) -> Result<Self, DecodeError> {
+        let length = read_field(stream, "subpackages length", 15)? as usize;
+        let offset = stream.position();
+        let mut substream = stream.sub_reader(length).ok_or(DecodeError {
+            offset,
+            kind: DecodeErrorKind::Truncated {
+                field: "subpackages",
+            },
+        })?;
+        let mut packages = Vec::new();
+        while !substream.is_empty() {
+            packages.push(Package::decode_nested(&mut substream, depth)?);
+        }
+        Ok(Self {
+            length_type: LengthType::TotalBits,
+            subpackages: Packages(packages),
         })
     }
 
-    fn decode_n(stream: &mut BitsStream) -> Option<Self> {
-        let n = usize::from_str_radix(&stream.take(11)?, 2).expect("Parseable number");
+    fn decode_n(stream: &mut BitReader<'_>, depth: usize) -> Result<Self, DecodeError> {
+        let n = read_field(stream, "subpackages count", 11)?;
         let mut packages = Vec::new();
         for _ in 0..n {
-            packages.push(Package::decode(stream)?);
+            packages.push(Package::decode_nested(stream, depth)?);
         }
-        Some(Self {
+        Ok(Self {
+            length_type: LengthType::Count,
             subpackages: Packages(packages),
         })
     }
+
+    /// Decode the operator body of a package nested `depth` operators deep
+    fn decode_nested(stream: &mut BitReader<'_>, depth: usize) -> Result<Self, DecodeError> {
+        if read_field(stream, "length type", 1)? == 1 {
+            Self::decode_n(stream, depth)
+        } else {
+            Self::decode_length(stream, depth)
+        }
+    }
 }
 
 impl Decode for OperatorPackage {
     type Output = OperatorPackage;
 
-    fn decode(stream: &mut BitsStream) -> Option<Self::Output> {
-        let length_type_id = stream.take(1)?;
-        match length_type_id.as_str() {
-            "0" => Self::decode_length(stream),
-            "1" => Self::decode_n(stream),
-            _ => unreachable!(),
+    fn decode(stream: &mut BitReader<'_>) -> Result<Self::Output, DecodeError> {
+        Self::decode_nested(stream, 0)
+    }
+}
+
+impl Package {
+    fn decode_nested(stream: &mut BitReader<'_>, depth: usize) -> Result<Self, DecodeError> {
+        let offset = stream.position();
+        let header = Header::decode(stream)?;
+        Ok(match header.package_type {
+            PackageType::Literal => Self::Literal((header, LiteralPackage::decode(stream)?)),
+            PackageType::Operator(id) => {
+                if depth >= MAX_DEPTH {
+                    return Err(DecodeError {
+                        offset,
+                        kind: DecodeErrorKind::TooDeep,
+                    });
+                }
+                let operator = OperatorPackage::decode_nested(stream, depth + 1)?;
+                let count = operator.subpackages.0.len();
+                if !takes_operands(id, count) {
+                    return Err(DecodeError {
+                        offset,
+                        kind: DecodeErrorKind::Operands { id, count },
+                    });
+                }
+                Self::Operator((header, operator))
+            }
+        })
+    }
+
+    /// Decode the outermost package of a transmission, which may only be followed by zeros
+    pub fn decode_transmission(bits_input: &BitsInput) -> Result<Self, DecodeError> {
+        let mut stream = bits_input.reader();
+        let package = Self::decode(&mut stream)?;
+        while !stream.is_empty() {
+            let offset = stream.position();
+            if stream.read_bit() == Some(true) {
+                return Err(DecodeError {
+                    offset,
+                    kind: DecodeErrorKind::TrailingBits,
+                });
+            }
         }
+        Ok(package)
     }
 }
 
 impl Decode for Package {
     type Output = Package;
 
-    fn decode(stream: &mut BitsStream) -> Option<Self::Output> {
-        let header = Header::decode(stream)?;
-        Some(match header.package_type {
-            PackageType::Literal => Self::Literal((header, LiteralPackage::decode(stream)?)),
-            PackageType::Operator(_) => Self::Operator((header, OperatorPackage::decode(stream)?)),
-        })
+    fn decode(stream: &mut BitReader<'_>) -> Result<Self::Output, DecodeError> {
+        Self::decode_nested(stream, 0)
     }
 }
 
-#[derive(Debug)]
-struct Packages(Vec<Package>);
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Packages(Vec<Package>);
 
 impl Packages {
-    fn version_sum(&self) -> usize {
+    pub fn as_slice(&self) -> &[Package] {
+        &self.0
+    }
+
+    pub fn version_sum(&self) -> usize {
         self.0.iter().map(|p| p.version_sum()).sum()
     }
+}
+
+/// Packages up to the end of the stream
+impl Decode for Packages {
+    type Output = Packages;
 
-    fn sum(&self) -> usize {
-        self.0.iter().map(|p| p.compute()).sum()
+    fn decode(stream: &mut BitReader<'_>) -> Result<Self::Output, DecodeError> {
+        let mut packages = Vec::new();
+        while !stream.is_empty() {
+            packages.push(Package::decode(stream)?);
+        }
+        Ok(Self(packages))
     }
+}
 
-    fn mul(&self) -> usize {
-        self.0.iter().map(|p| p.compute()).product()
+impl Encode for Header {
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError> {
+        if self.package_type == PackageType::Operator(LITERAL_ID) {
+            return Err(EncodeError::TypeMismatch {
+                id: LITERAL_ID,
+                package: "operator",
+            });
+        }
+        write_field(writer, "version", self.version.into(), 3)?;
+        write_field(writer, "type id", self.package_type.id().into(), 3)
     }
+}
 
-    fn min(&self) -> usize {
-        self.0.iter().map(|p| p.compute()).min().unwrap()
+impl Encode for LiteralPackage {
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError> {
+        let groups = self.number.to_radix_be(16);
+        for (i, group) in groups.iter().enumerate() {
+            writer.write_bit(i + 1 < groups.len());
+            writer.write((*group).into(), 4);
+        }
+        Ok(())
     }
+}
 
-    fn max(&self) -> usize {
-        self.0.iter().map(|p| p.compute()).max().unwrap()
+impl Encode for OperatorPackage {
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError> {
+        match self.length_type {
+            LengthType::TotalBits => {
+                let mut subpackages = BitWriter::new();
+                self.subpackages.encode(&mut subpackages)?;
+                writer.write_bit(false);
+                write_field(writer, "subpackages length", subpackages.len(), 15)?;
+                writer.append(&subpackages);
+                Ok(())
+            }
+            LengthType::Count => {
+                writer.write_bit(true);
+                write_field(writer, "subpackages count", self.subpackages.0.len(), 11)?;
+                self.subpackages.encode(writer)
+            }
+        }
     }
 }
 
-impl Decode for Packages {
-    type Output = Packages;
+impl Encode for Package {
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError> {
+        let (header, package) = match self {
+            Package::Operator((header, _)) => (header, "operator"),
+            Package::Literal((header, _)) => (header, "literal"),
+        };
+        // The header type decides how the package is read back
+        if (header.package_type == PackageType::Literal) != matches!(self, Package::Literal(_)) {
+            return Err(EncodeError::TypeMismatch {
+                id: header.package_type.id(),
+                package,
+            });
+        }
+        match self {
+            Package::Operator((header, operator)) => {
+                header.encode(writer)?;
+                operator.encode(writer)
+            }
+            Package::Literal((header, literal)) => {
+                header.encode(writer)?;
+                literal.encode(writer)
+            }
+        }
+    }
+}
 
-    fn decode(stream: &mut BitsStream) -> Option<Self::Output> {
-        let mut packages = Vec::new();
-        while let Some(package) = Package::decode(stream) {
-            packages.push(package);
+impl Encode for Packages {
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError> {
+        self.0.iter().try_for_each(|package| package.encode(writer))
+    }
+}
+
+impl Package {
+    /// The transmission of this package alone, padded with zeros to whole bytes
+    pub fn to_hex(&self) -> Result<String, EncodeError> {
+        let mut writer = BitWriter::new();
+        self.encode(&mut writer)?;
+        Ok(BitsInput::from(writer).to_string())
+    }
+
+    /// Indented view of the package and its subpackages, one per line
+    pub fn tree(&self) -> PackageTree<'_> {
+        PackageTree(self)
+    }
+}
+
+/// S-expression of the package, such as `(max (+ 1 2) (* 3 4))`
+impl Display for Package {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Package::Literal((_, literal)) => write!(f, "{}", literal.number),
+            Package::Operator((header, operator)) => {
+                write!(f, "({}", header.package_type)?;
+                for package in &operator.subpackages.0 {
+                    write!(f, " {}", package)?;
+                }
+                write!(f, ")")
+            }
         }
-        if packages.is_empty() {
-            None
-        } else {
-            Some(Self(packages))
+    }
+}
+
+/// See [`Package::tree`]
+pub struct PackageTree<'package>(&'package Package);
+
+impl PackageTree<'_> {
+    fn fmt_at(package: &Package, depth: usize, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+        match package {
+            Package::Literal((header, literal)) => {
+                writeln!(f, "v{} literal {}", header.version, literal.number)
+            }
+            Package::Operator((header, operator)) => {
+                let length_type = match operator.length_type {
+                    LengthType::TotalBits => "total bits",
+                    LengthType::Count => "count",
+                };
+                writeln!(
+                    f,
+                    "v{} {} by {}",
+                    header.version, header.package_type, length_type
+                )?;
+                operator
+                    .subpackages
+                    .0
+                    .iter()
+                    .try_for_each(|package| Self::fmt_at(package, depth + 1, f))
+            }
+        }
+    }
+}
+
+impl Display for PackageTree<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Self::fmt_at(self.0, 0, f)
+    }
+}
+
+/// Value and version sum of a package evaluated straight from its bits
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Evaluation<N> {
+    pub value: N,
+    pub version_sum: usize,
+}
+
+/// Why a transmission can't be evaluated in a single pass
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum StreamError {
+    Decode(DecodeError),
+    Eval(EvalError),
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Decode(error) => write!(f, "{}", error),
+            StreamError::Eval(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<DecodeError> for StreamError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl From<EvalError> for StreamError {
+    fn from(error: EvalError) -> Self {
+        Self::Eval(error)
+    }
+}
+
+/// Where the subpackages of an operator being evaluated end
+enum Body<'bytes> {
+    /// The bits left for them
+    Bits(BitReader<'bytes>),
+    /// How many are left
+    Count(u64),
+}
+
+/// An operator whose subpackages are being read
+struct Frame<'bytes, N> {
+    /// Where the operator starts, for errors
+    offset: usize,
+    /// Index among the subpackages of the enclosing operator
+    index: usize,
+    id: u8,
+    body: Body<'bytes>,
+    count: usize,
+    /// Subpackages folded so far, the first operand of a comparison until the second comes
+    value: Option<N>,
+}
+
+impl<N: Natural> Frame<'_, N> {
+    fn is_done(&self) -> bool {
+        match &self.body {
+            Body::Bits(reader) => reader.is_empty(),
+            Body::Count(n) => *n == 0,
+        }
+    }
+
+    /// Fold the value of the next subpackage, `outer` are the operators enclosing this one
+    fn push(&mut self, value: N, outer: &[Frame<'_, N>]) -> Result<(), EvalError> {
+        if let Body::Count(n) = &mut self.body {
+            *n -= 1;
+        }
+        self.count += 1;
+        let Some(folded) = self.value.take() else {
+            self.value = Some(value);
+            return Ok(());
+        };
+        let overflow = |operation| EvalError::Overflow {
+            path: path_below(outer, self.index),
+            offset: Some(self.offset),
+            operation,
+        };
+        self.value = Some(match self.id {
+            0 => folded.checked_add(&value).ok_or_else(|| overflow("sum"))?,
+            1 => folded
+                .checked_mul(&value)
+                .ok_or_else(|| overflow("product"))?,
+            2 => folded.min(value),
+            3 => folded.max(value),
+            // Comparisons of more than two operands are rejected when the operator ends
+            id if self.count == 2 => {
+                let holds = match id {
+                    5 => folded > value,
+                    6 => folded < value,
+                    _ => folded == value,
+                };
+                if holds {
+                    N::one()
+                } else {
+                    N::zero()
+                }
+            }
+            _ => folded,
+        });
+        Ok(())
+    }
+
+    fn finish(self) -> Result<N, DecodeError> {
+        if !takes_operands(self.id, self.count) {
+            return Err(DecodeError {
+                offset: self.offset,
+                kind: DecodeErrorKind::Operands {
+                    id: self.id,
+                    count: self.count,
+                },
+            });
+        }
+        Ok(self.value.unwrap_or_else(|| match self.id {
+            1 => N::one(),
+            _ => N::zero(),
+        }))
+    }
+}
+
+/// Reader of the innermost operator delimited by bits, or of the whole stream
+fn current_reader<'s, 'bytes, N>(
+    frames: &'s mut [Frame<'bytes, N>],
+    root: &'s mut BitReader<'bytes>,
+) -> &'s mut BitReader<'bytes> {
+    frames
+        .iter_mut()
+        .rev()
+        .find_map(|frame| match &mut frame.body {
+            Body::Bits(reader) => Some(reader),
+            Body::Count(_) => None,
+        })
+        .unwrap_or(root)
+}
+
+/// Path to the subpackage `index` of the innermost operator, the outermost package if there
+/// are no operators
+fn path_below<N>(frames: &[Frame<'_, N>], index: usize) -> Vec<usize> {
+    let mut path: Vec<usize> = frames.iter().skip(1).map(|frame| frame.index).collect();
+    if !frames.is_empty() {
+        path.push(index);
+    }
+    path
+}
+
+/// Evaluate the next package of `stream` as its bits are read, without building it.
+/// Operators waiting for subpackages are kept on a stack, folded as values come, so
+/// nesting is bounded by memory rather than [`MAX_DEPTH`].
+/// Values match [`Package::evaluate`], though of several errors another one may be reported.
+pub fn evaluate_stream<N: Natural>(
+    stream: &mut BitReader<'_>,
+) -> Result<Evaluation<N>, StreamError> {
+    let mut frames: Vec<Frame<'_, N>> = Vec::new();
+    let mut version_sum = 0;
+    loop {
+        let index = frames.last().map_or(0, |frame| frame.count);
+        let reader = current_reader(&mut frames, stream);
+        let offset = reader.position();
+        let header = Header::decode(reader)?;
+        version_sum += header.version as usize;
+
+        let mut value = match header.package_type {
+            PackageType::Literal => {
+                let sixteen = N::from_usize(16);
+                let mut number = Some(N::zero());
+                loop {
+                    let group = read_field(reader, "literal group", 5)? as usize;
+                    number = number
+                        .zip(sixteen.as_ref())
+                        .and_then(|(n, sixteen)| n.checked_mul(sixteen))
+                        .zip(N::from_usize(group & 0xF))
+                        .and_then(|(n, digit)| n.checked_add(&digit));
+                    if group >> 4 == 0 {
+                        break;
+                    }
+                }
+                number.ok_or_else(|| EvalError::Overflow {
+                    path: path_below(&frames, index),
+                    offset: Some(offset),
+                    operation: "literal",
+                })?
+            }
+            PackageType::Operator(id) => {
+                let body = if read_field(reader, "length type", 1)? == 1 {
+                    Body::Count(read_field(reader, "subpackages count", 11)?)
+                } else {
+                    let length = read_field(reader, "subpackages length", 15)? as usize;
+                    let position = reader.position();
+                    Body::Bits(reader.sub_reader(length).ok_or(DecodeError {
+                        offset: position,
+                        kind: DecodeErrorKind::Truncated {
+                            field: "subpackages",
+                        },
+                    })?)
+                };
+                let frame = Frame {
+                    offset,
+                    index,
+                    id,
+                    body,
+                    count: 0,
+                    value: None,
+                };
+                if !frame.is_done() {
+                    frames.push(frame);
+                    continue;
+                }
+                frame.finish()?
+            }
+        };
+
+        // Hand the value up through every operator it completes
+        loop {
+            let Some((top, outer)) = frames.split_last_mut() else {
+                return Ok(Evaluation { value, version_sum });
+            };
+            top.push(value, outer)?;
+            if !top.is_done() {
+                break;
+            }
+            value = frames.pop().expect("A frame on top").finish()?;
         }
     }
 }
 
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Package;
+    type PartOne = usize;
+    type PartTwo = BigUint;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        let bits_input: BitsInput = io::read_object(reader)?;
+        Package::decode_transmission(&bits_input).map_err(error::Error::parse)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        input.version_sum()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        input
+            .evaluate()
+            .expect("Decoded packets are valid and big numbers don't overflow")
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::day_16::{
-        BitsInput, Decode, Header, LiteralPackage, OperatorPackage, Package, PackageType, Packages,
+        evaluate_stream, BitsInput, Decode, DecodeError, DecodeErrorKind, EncodeError, EvalError,
+        Evaluation, Header, LengthType, LiteralPackage, OperatorPackage, Package, PackageType,
+        Packages, StreamError, MAX_DEPTH,
     };
+    use crate::utils::bits::BitWriter;
     use crate::utils::io;
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
     use std::path::PathBuf;
     use std::str::FromStr;
 
+    fn decode_hex(hex: &str) -> Package {
+        let bits_input: BitsInput = hex.parse().expect("Bitsinput should parse");
+        Package::decode(&mut bits_input.reader()).expect("A package")
+    }
+
+    fn literal(version: u8, number: u128) -> Package {
+        Package::Literal((
+            Header {
+                version,
+                package_type: PackageType::Literal,
+            },
+            LiteralPackage {
+                number: number.into(),
+            },
+        ))
+    }
+
+    fn package() -> impl Strategy<Value = Package> {
+        let leaf = (0u8..8, any::<u128>()).prop_map(|(version, number)| literal(version, number));
+        leaf.prop_recursive(4, 32, 4, |inner| {
+            (
+                0u8..8,
+                prop::sample::select(vec![0u8, 1, 2, 3, 5, 6, 7]),
+                any::<bool>(),
+                prop::collection::vec(inner, 1..4),
+            )
+                .prop_map(|(version, id, count, mut subpackages)| {
+                    if (5..=7).contains(&id) {
+                        subpackages.resize(2, literal(version, 0));
+                    }
+                    Package::Operator((
+                        Header {
+                            version,
+                            package_type: PackageType::Operator(id),
+                        },
+                        OperatorPackage {
+                            length_type: if count {
+                                LengthType::Count
+                            } else {
+                                LengthType::TotalBits
+                            },
+                            subpackages: Packages(subpackages),
+                        },
+                    ))
+                })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn decode_encoded(package in package()) {
+            let hex = package.to_hex().unwrap();
+            prop_assert_eq!(hex.len() % 2, 0);
+            prop_assert_eq!(decode_hex(&hex), package);
+        }
+
+        #[test]
+        fn stream_matches_tree(package in package()) {
+            let bits_input: BitsInput = package.to_hex().unwrap().parse().unwrap();
+            let big = evaluate_stream::<BigUint>(&mut bits_input.reader()).unwrap();
+            prop_assert_eq!(big.value, package.evaluate::<BigUint>().unwrap());
+            prop_assert_eq!(big.version_sum, package.version_sum());
+            let words = evaluate_stream::<u64>(&mut bits_input.reader());
+            match package.evaluate::<u64>() {
+                Ok(value) => prop_assert_eq!(words.unwrap().value, value),
+                Err(_) => prop_assert!(matches!(words, Err(StreamError::Eval(_)))),
+            }
+        }
+    }
+
+    fn operator(id: u8, subpackages: Vec<Package>) -> Package {
+        Package::Operator((
+            Header {
+                version: 0,
+                package_type: PackageType::Operator(id),
+            },
+            OperatorPackage::new(subpackages),
+        ))
+    }
+
+    #[test]
+    fn overflow_is_located() {
+        let max = u64::MAX as u128;
+        let product = operator(
+            0,
+            vec![
+                literal(0, 1),
+                operator(1, vec![literal(0, max), literal(0, 2)]),
+            ],
+        );
+        let error = product.evaluate::<u64>().unwrap_err();
+        assert_eq!(
+            error,
+            EvalError::Overflow {
+                path: vec![1],
+                offset: None,
+                operation: "product"
+            }
+        );
+        assert_eq!(error.to_string(), "product overflows in packet root.1");
+        assert_eq!(
+            product.evaluate::<BigUint>().unwrap(),
+            BigUint::from(max * 2 + 1)
+        );
+
+        let long = operator(3, vec![literal(0, 7), literal(0, max + 1)]);
+        assert_eq!(
+            long.evaluate::<u64>().unwrap_err(),
+            EvalError::Overflow {
+                path: vec![1],
+                offset: None,
+                operation: "literal"
+            }
+        );
+        let decoded = decode_hex(&long.to_hex().unwrap());
+        assert_eq!(decoded.evaluate::<u128>(), Ok(max + 1));
+    }
+
+    #[test]
+    fn invalid_operators_are_errors() {
+        let lonely = operator(0, vec![operator(6, vec![literal(0, 1)])]);
+        let error = lonely.compute().unwrap_err();
+        assert_eq!(
+            error,
+            EvalError::Operands {
+                path: vec![0],
+                id: 6,
+                count: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "operator 6 can't take 1 subpackages in packet root.0"
+        );
+        assert!(operator(2, vec![]).compute().is_err());
+        assert!(operator(200, vec![literal(0, 1)]).compute().is_err());
+        assert_eq!(operator(0, vec![]).compute(), Ok(0));
+
+        // The encoder doesn't check operands, the decoder does
+        let hex = operator(3, vec![]).to_hex().unwrap();
+        let bits_input: BitsInput = hex.parse().unwrap();
+        assert_eq!(
+            Package::decode_transmission(&bits_input).unwrap_err(),
+            DecodeError {
+                offset: 0,
+                kind: DecodeErrorKind::Operands { id: 3, count: 0 }
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_transmissions() {
+        let decode = |hex: &str| Package::decode_transmission(&hex.parse().unwrap());
+        let truncated = |offset, field| DecodeError {
+            offset,
+            kind: DecodeErrorKind::Truncated { field },
+        };
+        assert_eq!(decode(""), Err(truncated(0, "version")));
+        // The last group of 2021 is cut
+        assert_eq!(decode("D2FE2"), Err(truncated(16, "literal group")));
+        // The length of the subpackages goes past the end
+        assert_eq!(decode("38006F452912"), Err(truncated(22, "subpackages")));
+        assert_eq!(decode("EE00D40C8230"), Err(truncated(46, "literal group")));
+        assert_eq!(
+            decode("D2FE2801"),
+            Err(DecodeError {
+                offset: 31,
+                kind: DecodeErrorKind::TrailingBits
+            })
+        );
+        assert!(decode("D2FE2800").is_ok());
+
+        let error = decode("D2FE2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "transmission ends in the literal group at bit 16"
+        );
+        assert!("d2fe28".parse::<BitsInput>().is_ok());
+        assert!("D2FE28?".parse::<BitsInput>().is_err());
+    }
+
+    #[test]
+    fn nesting_is_bounded() {
+        let mut package = literal(0, 1);
+        for _ in 0..MAX_DEPTH {
+            package = operator(0, vec![package]);
+        }
+        assert!(decode_hex(&package.to_hex().unwrap()).compute().is_ok());
+
+        // Unbounded nesting, each level a sum with one subpackage
+        let mut writer = BitWriter::new();
+        for _ in 0..=MAX_DEPTH {
+            // Version 0, type id 0, counted subpackages
+            writer.write(0b0000001, 7);
+            writer.write(1, 11);
+        }
+        let bits_input = BitsInput::from(writer);
+        assert_eq!(
+            Package::decode_transmission(&bits_input).unwrap_err(),
+            DecodeError {
+                offset: MAX_DEPTH * 18,
+                kind: DecodeErrorKind::TooDeep
+            }
+        );
+    }
+
+    #[test]
+    fn stream_examples() {
+        let stream = |hex: &str| {
+            let bits_input: BitsInput = hex.parse().unwrap();
+            evaluate_stream::<u64>(&mut bits_input.reader())
+        };
+        for (hex, value) in [
+            ("C200B40A82", 3),
+            ("04005AC33890", 54),
+            ("880086C3E88112", 7),
+            ("CE00C43D881120", 9),
+            ("D8005AC2A8F0", 1),
+            ("F600BC2D8F", 0),
+            ("9C005AC2F8F0", 0),
+            ("9C0141080250320F1802104A08", 1),
+        ] {
+            assert_eq!(stream(hex).unwrap().value, value, "{}", hex);
+        }
+        assert_eq!(
+            stream("A0016C880162017C3686B18A3D4780"),
+            Ok(Evaluation {
+                value: 54,
+                version_sum: 31
+            })
+        );
+        assert_eq!(
+            stream("38006F452912"),
+            Err(StreamError::Decode(DecodeError {
+                offset: 22,
+                kind: DecodeErrorKind::Truncated {
+                    field: "subpackages"
+                }
+            }))
+        );
+
+        let max = u64::MAX as u128;
+        let product = operator(
+            0,
+            vec![
+                literal(0, 1),
+                operator(1, vec![literal(0, max), literal(0, 2)]),
+                literal(0, max + 1),
+            ],
+        );
+        let error = stream(&product.to_hex().unwrap()).unwrap_err();
+        assert_eq!(
+            error,
+            StreamError::Eval(EvalError::Overflow {
+                path: vec![1],
+                offset: Some(29),
+                operation: "product"
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "product overflows in packet root.1 at bit 29"
+        );
+        let long = operator(3, vec![literal(0, 7), literal(0, max + 1)]);
+        assert_eq!(
+            stream(&long.to_hex().unwrap()),
+            Err(StreamError::Eval(EvalError::Overflow {
+                path: vec![1],
+                offset: Some(29),
+                operation: "literal"
+            }))
+        );
+    }
+
+    #[test]
+    fn stream_nests_deeper() {
+        let depth = MAX_DEPTH * 4;
+        let mut writer = BitWriter::new();
+        for _ in 0..depth {
+            // Version 1, type id 0, counted subpackages
+            writer.write(0b0010001, 7);
+            writer.write(1, 11);
+        }
+        // Version 1 literal 5
+        writer.write(0b001100, 6);
+        writer.write(0b00101, 5);
+        let bits_input = BitsInput::from(writer);
+        assert_eq!(
+            evaluate_stream::<u32>(&mut bits_input.reader()),
+            Ok(Evaluation {
+                value: 5,
+                version_sum: depth + 1
+            })
+        );
+    }
+
+    #[test]
+    fn display_trees() {
+        let package = decode_hex("9C0141080250320F1802104A08");
+        assert_eq!(package.to_string(), "(= (+ 1 3) (* 2 2))");
+        assert_eq!(
+            package.tree().to_string(),
+            "\
+v4 = by total bits
+  v2 + by count
+    v2 literal 1
+    v4 literal 3
+  v6 * by count
+    v0 literal 2
+    v2 literal 2
+"
+        );
+        let unknown = operator(200, vec![literal(0, 1), literal(0, 2)]);
+        assert_eq!(unknown.to_string(), "(type 200 1 2)");
+    }
+
+    #[test]
+    fn encode_examples() {
+        for hex in [
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "8A004A801A8002F478",
+        ] {
+            assert_eq!(decode_hex(hex).to_hex().unwrap(), hex);
+        }
+    }
+
+    #[test]
+    fn encode_picks_length_type() {
+        let many = OperatorPackage::new(vec![literal(0, 1); 3000]);
+        assert_eq!(many.length_type, LengthType::TotalBits);
+        let few = OperatorPackage::new(vec![literal(0, 1); 2]);
+        assert_eq!(few.length_type, LengthType::Count);
+
+        let too_long = Package::Operator((
+            Header {
+                version: 0,
+                package_type: PackageType::Operator(0),
+            },
+            many,
+        ));
+        assert_eq!(
+            too_long.to_hex().unwrap_err(),
+            EncodeError::TooWide {
+                field: "subpackages length",
+                value: 3000 * 11,
+                bits: 15
+            }
+        );
+        assert!(literal(8, 1).to_hex().is_err());
+    }
+
+    #[test]
+    fn encode_rejects_mismatched_types() {
+        let subpackages = vec![literal(0, 1), literal(0, 2)];
+        let literal_id = Package::Operator((
+            Header::new(0, PackageType::Operator(4)),
+            OperatorPackage::new(subpackages.clone()),
+        ));
+        assert_eq!(
+            literal_id.to_hex().unwrap_err(),
+            EncodeError::TypeMismatch {
+                id: 4,
+                package: "operator"
+            }
+        );
+        let literal_header = Package::Operator((
+            Header::new(0, PackageType::Literal),
+            OperatorPackage::new(subpackages),
+        ));
+        assert_eq!(
+            literal_header.to_hex().unwrap_err(),
+            EncodeError::TypeMismatch {
+                id: 4,
+                package: "operator"
+            }
+        );
+        let operator_header = Package::Literal((
+            Header::new(0, PackageType::Operator(0)),
+            LiteralPackage::new(BigUint::from(1u8)),
+        ));
+        assert_eq!(
+            operator_header.to_hex().unwrap_err(),
+            EncodeError::TypeMismatch {
+                id: 0,
+                package: "literal"
+            }
+        );
+    }
+
+    #[test]
+    fn bits_input_is_packed() {
+        let bits_input: BitsInput = "D2FE2".parse().unwrap();
+        let mut reader = bits_input.reader();
+        assert_eq!(reader.remaining(), 20);
+        assert_eq!(reader.read(20), Some(0xD2FE2));
+        assert!("D2FG28".parse::<BitsInput>().is_err());
+    }
+
     #[test]
     fn parse_literal_with_header() {
         let input = "D2FE28";
         let bits_input: BitsInput = input.parse().expect("Bitsinput should parse");
-        let mut stream = bits_input.into_bits_stream();
+        let mut stream = bits_input.reader();
         let header = Header::decode(&mut stream).unwrap();
         let expected_header = Header {
             version: 6,
@@ -343,14 +1427,14 @@ mod test {
         };
         assert_eq!(header, expected_header);
         let literal = LiteralPackage::decode(&mut stream).unwrap();
-        assert_eq!(literal.number, 2021);
+        assert_eq!(literal.number, BigUint::from(2021u32));
     }
 
     #[test]
     fn parse_operator_with_header() {
         let input = "38006F45291200";
         let bits_input: BitsInput = input.parse().expect("Bitsinput should parse");
-        let mut stream = bits_input.into_bits_stream();
+        let mut stream = bits_input.reader();
         let header = Header::decode(&mut stream).unwrap();
         let expected_header = Header {
             version: 1,
@@ -369,7 +1453,7 @@ mod test {
     fn parse_operator_with_header_other() {
         let input = "EE00D40C823060";
         let bits_input: BitsInput = input.parse().expect("Bitsinput should parse");
-        let mut stream = bits_input.into_bits_stream();
+        let mut stream = bits_input.reader();
         let header = Header::decode(&mut stream).unwrap();
         let expected_header = Header {
             version: 7,
@@ -388,9 +1472,8 @@ mod test {
     fn part_1() -> std::io::Result<()> {
         let bits_input: BitsInput =
             io::read_object_from_file(&PathBuf::from_str("./inputs/day_16.txt").unwrap())?;
-        let mut stream = bits_input.into_bits_stream();
-        let packages = Packages::decode(&mut stream).unwrap();
-        println!("Day 16 part 1 solution: {}", packages.version_sum());
+        let package = Package::decode_transmission(&bits_input).unwrap();
+        println!("Day 16 part 1 solution: {}", package.version_sum());
         Ok(())
     }
 
@@ -398,10 +1481,17 @@ mod test {
     fn part_2() -> std::io::Result<()> {
         let bits_input: BitsInput =
             io::read_object_from_file(&PathBuf::from_str("./inputs/day_16.txt").unwrap())?;
-        let mut stream = bits_input.into_bits_stream();
-        let package = Package::decode(&mut stream).unwrap();
+        let package = Package::decode_transmission(&bits_input).unwrap();
         println!("{:?}", package);
-        println!("Day 16 part 2 solution: {}", package.compute());
+        println!("Day 16 part 2 solution: {}", package.compute().unwrap());
+        let streamed = evaluate_stream(&mut bits_input.reader()).unwrap();
+        assert_eq!(
+            streamed,
+            Evaluation {
+                value: package.compute().unwrap(),
+                version_sum: package.version_sum()
+            }
+        );
         Ok(())
     }
 }
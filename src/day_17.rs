@@ -1,9 +1,41 @@
-use sscanf::const_format::pmr::Range;
+use crate::error;
+use crate::error::ParseError;
+use crate::solution::Solution;
+use crate::utils::io;
 use std::collections::HashSet;
+use std::io::BufRead;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 type InitialVelocity = isize;
 type MaxT = usize;
 
+#[derive(Debug, Clone)]
+pub struct TargetArea {
+    x: RangeInclusive<isize>,
+    y: RangeInclusive<isize>,
+}
+
+impl FromStr for TargetArea {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x_start, x_end, y_start, y_end) = sscanf::scanf!(
+            s.trim(),
+            "target area: x={}..{}, y={}..{}",
+            isize,
+            isize,
+            isize,
+            isize
+        )
+        .ok_or_else(|| ParseError::new("target area: x=<from>..<to>, y=<from>..<to>", s))?;
+        Ok(Self {
+            x: x_start..=x_end,
+            y: y_start..=y_end,
+        })
+    }
+}
+
 fn solve_x(
     start: isize,
     end: isize,
@@ -42,10 +74,13 @@ fn solve_x(
         .collect()
 }
 
+// Unfinished closed form take on part 1, see the commented out example test
+#[allow(dead_code)]
 fn initial_y_speed(initial_y: isize, final_y: isize, g: isize, t: usize) -> isize {
     (final_y - initial_y + (g * (t as isize * t as isize) / 2)) / t as isize
 }
 
+#[allow(dead_code)]
 fn solve_y(
     ts: &[(InitialVelocity, MaxT)],
     starting_position: isize,
@@ -60,9 +95,8 @@ fn solve_y(
                         initial_y_speed(starting_position, final_y as isize, g as isize, *t_max);
                     (0usize..=*t_max)
                         .map(|t| {
-                            let heigh = starting_position + initial_speed * t as isize
-                                - (t as isize * t as isize) / 2;
-                            heigh
+                            starting_position + initial_speed * t as isize
+                                - (t as isize * t as isize) / 2
                         })
                         .max()
                         .unwrap()
@@ -109,12 +143,9 @@ fn exploit_y(
         .collect()
 }
 
-fn solve() -> (isize, usize) {
-    let x_data = solve_x(1, 171, 150, 171);
-    let max_t = x_data.iter().map(|(_, t)| *t).max().unwrap();
-    let y_data = exploit_y(1, 10000, -70, -129);
-    println!("{:?}", x_data);
-    println!("{:?}", y_data);
+pub fn solve(target: &TargetArea) -> (isize, usize) {
+    let x_data = solve_x(1, *target.x.end(), *target.x.start(), *target.x.end());
+    let y_data = exploit_y(1, 10000, *target.y.end(), *target.y.start());
     (
         y_data.iter().map(|(m, _, _)| *m).max().unwrap(),
         // TODO: sencond part is wrong approach
@@ -128,13 +159,37 @@ fn solve() -> (isize, usize) {
 }
 
 //fancy tiny solver found on google :/
+#[allow(dead_code)]
 fn solve_part_1_smart() -> isize {
     (129 * (129 - 1)) / 2
 }
 
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = TargetArea;
+    type PartOne = isize;
+    type PartTwo = usize;
+
+    fn parse(&self, reader: impl BufRead) -> error::Result<Self::Input> {
+        io::read_object(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne {
+        solve(input).0
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo {
+        solve(input).1
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::day_17::{solve, solve_x};
+    use crate::day_17::{solve, TargetArea};
+    use crate::utils::io;
+    use std::path::PathBuf;
+    use std::str::FromStr;
 
     // #[test]
     // fn example_part_1() {
@@ -151,7 +206,10 @@ mod test {
     // }
 
     #[test]
-    fn part_1() {
-        println!("Day 1 part 1 solution: {:?}", solve());
+    fn part_1() -> std::io::Result<()> {
+        let target: TargetArea =
+            io::read_object_from_file(&PathBuf::from_str("./inputs/day_17.txt").unwrap())?;
+        println!("Day 1 part 1 solution: {:?}", solve(&target));
+        Ok(())
     }
 }
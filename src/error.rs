@@ -0,0 +1,162 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input couldn't be read
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// The input was read but it couldn't be parsed.
+    /// `line` is 1-based and, as `text`, only known for line by line inputs.
+    Parse {
+        path: Option<PathBuf>,
+        line: Option<usize>,
+        text: Option<String>,
+        source: BoxedError,
+    },
+}
+
+impl Error {
+    pub fn parse_line(line: usize, text: &str, source: impl Into<BoxedError>) -> Self {
+        Self::Parse {
+            path: None,
+            line: Some(line),
+            text: Some(text.to_string()),
+            source: source.into(),
+        }
+    }
+
+    pub fn parse(source: impl Into<BoxedError>) -> Self {
+        Self::Parse {
+            path: None,
+            line: None,
+            text: None,
+            source: source.into(),
+        }
+    }
+
+    /// Attach the file the error comes from, if it wasn't known already
+    pub fn with_path(mut self, new_path: &Path) -> Self {
+        match &mut self {
+            Error::Io { path, .. } | Error::Parse { path, .. } => {
+                path.get_or_insert_with(|| new_path.to_path_buf());
+            }
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => {
+                write!(f, "Couldn't read input")?;
+                if let Some(path) = path {
+                    write!(f, " {}", path.display())?;
+                }
+                write!(f, ": {}", source)
+            }
+            Error::Parse {
+                path,
+                line,
+                text,
+                source,
+            } => {
+                write!(f, "Couldn't parse input")?;
+                match (path, line) {
+                    (Some(path), Some(line)) => write!(f, " {}:{}", path.display(), line)?,
+                    (Some(path), None) => write!(f, " {}", path.display())?,
+                    (None, Some(line)) => write!(f, " at line {}", line)?,
+                    (None, None) => {}
+                }
+                if let Some(text) = text {
+                    write!(f, " `{}`", text)?;
+                }
+                write!(f, ": {}", source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Self::Io { path: None, source }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(source: ParseError) -> Self {
+        Self::parse(source)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        let kind = match &error {
+            Error::Io { source, .. } => source.kind(),
+            Error::Parse { .. } => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+/// A piece of input that doesn't have the shape a parser expects.
+/// `line` and `column` are 1-based and relative to the text given to the parser.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    expected: String,
+    found: String,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+impl ParseError {
+    pub fn new(expected: impl Into<String>, found: impl Into<String>) -> Self {
+        Self {
+            expected: expected.into(),
+            found: found.into(),
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = Some(column);
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {}, found `{}`", self.expected, self.found)?;
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, " at line {}, column {}", line, column),
+            (Some(line), None) => write!(f, " at line {}", line),
+            (None, Some(column)) => write!(f, " at column {}", column),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,107 @@
+use crate::error;
+use crate::solution::{solution, DynSolution};
+use crate::utils::io::Input;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const INPUTS_DIR: &str = "./inputs";
+const MANIFEST: &str = "examples.toml";
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    example: Vec<Example>,
+}
+
+/// A puzzle example, its input is either `input` inline or a `file` relative to the manifest
+#[derive(Debug, Deserialize)]
+struct Example {
+    day: u8,
+    name: Option<String>,
+    input: Option<String>,
+    file: Option<PathBuf>,
+    part_one: Option<String>,
+    part_two: Option<String>,
+}
+
+impl Example {
+    fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("day {} ({})", self.day, name),
+            None => format!("day {}", self.day),
+        }
+    }
+
+    fn input(&self, dir: &Path) -> Input {
+        match (&self.input, &self.file) {
+            (Some(input), None) => Input::inline(input.as_str()),
+            (None, Some(file)) => Input::file(dir.join(file)),
+            _ => panic!("{} needs exactly one of `input` or `file`", self.label()),
+        }
+    }
+
+    /// Check every known answer, returning a description of each mismatch
+    fn check(&self, dir: &Path) -> error::Result<Vec<String>> {
+        let solution: &dyn DynSolution =
+            solution(self.day).unwrap_or_else(|| panic!("{} isn't a registered day", self.label()));
+        let parsed = self
+            .input(dir)
+            .parse_with(|reader| solution.run_parse(reader))?;
+
+        let mut failures = Vec::new();
+        for (part, expected) in [(1, &self.part_one), (2, &self.part_two)] {
+            if let Some(expected) = expected {
+                let result = match part {
+                    1 => solution.run_part_one(parsed.as_ref()),
+                    _ => solution.run_part_two(parsed.as_ref()),
+                };
+                if &result != expected {
+                    failures.push(format!(
+                        "{} part {}: expected {:?}, got {:?}",
+                        self.label(),
+                        part,
+                        expected,
+                        result
+                    ));
+                }
+            }
+        }
+        Ok(failures)
+    }
+}
+
+fn load_manifest(dir: &Path) -> Manifest {
+    let content = std::fs::read_to_string(dir.join(MANIFEST)).expect("Readable examples manifest");
+    toml::from_str(&content).expect("Valid examples manifest")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fixtures::{load_manifest, INPUTS_DIR};
+    use crate::solution::solutions;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    #[test]
+    fn every_day_has_an_example() {
+        let manifest = load_manifest(Path::new(INPUTS_DIR));
+        let days: HashSet<u8> = manifest.example.iter().map(|e| e.day).collect();
+        for (day, _) in solutions() {
+            assert!(days.contains(&day), "Day {} has no example", day);
+        }
+    }
+
+    #[test]
+    fn examples() {
+        let dir = Path::new(INPUTS_DIR);
+        let failures: Vec<String> = load_manifest(dir)
+            .example
+            .iter()
+            .flat_map(|example| {
+                example
+                    .check(dir)
+                    .unwrap_or_else(|e| vec![format!("{}: {}", example.label(), e)])
+            })
+            .collect();
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,31 @@
+//! Advent of Code 2021 solutions as a library.
+//!
+//! Every `day_XX` module exposes the parsed input of its puzzle and the solvers of both parts.
+//! [`mod@solution`] registers all of them behind the common [`Solution`] trait, so a day can be
+//! parsed and run without knowing its types.
+
+pub mod day_01;
+pub mod day_02;
+pub mod day_03;
+pub mod day_04;
+pub mod day_05;
+pub mod day_06;
+pub mod day_07;
+pub mod day_08;
+pub mod day_09;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod day_17;
+pub mod error;
+#[cfg(test)]
+mod fixtures;
+pub mod solution;
+pub mod utils;
+
+pub use error::{Error, ParseError, Result};
+pub use solution::{solution, solutions, DynSolution, Solution, DAYS};
@@ -1,27 +1,164 @@
-#![feature(bool_to_option)]
-#![feature(vec_retain_mut)]
-#![feature(int_abs_diff)]
-#![feature(once_cell)]
-
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
-mod day_16;
-mod day_17;
-mod utils;
-
-fn main() {
-    println!("Hello, world!");
+mod answers;
+mod bench;
+
+use advent_code_2021::utils::io::Input;
+use advent_code_2021::{error, solution, DAYS};
+use answers::{Answers, Verdict, ANSWERS_FILE};
+use clap::{Parser, Subcommand};
+use std::path::Path;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(about = "Advent of Code 2021 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the solvers and print one result line per part
+    Run {
+        /// Day to run
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=DAYS as i64), required_unless_present = "all")]
+        day: Option<u8>,
+        /// Only run this part of the day
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2), requires = "day")]
+        part: Option<u8>,
+        /// Puzzle input: a file, a `.gz` compressed file or `-` for stdin.
+        /// Defaults to `inputs/day_XX.txt`
+        #[arg(long, requires = "day")]
+        input: Option<Input>,
+        /// Run every day with its default input
+        #[arg(long, conflicts_with = "day")]
+        all: bool,
+        /// Add the answer of the named part to `inputs/answers.toml` if it's missing,
+        /// once it has been confirmed on the puzzle site
+        #[arg(long, conflicts_with = "input", requires_all = ["day", "part"])]
+        record: bool,
+    },
+    /// Time parsing and each part of the solvers over repeated runs
+    Bench {
+        /// Day to bench, every day if omitted
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=DAYS as i64))]
+        day: Option<u8>,
+        /// Puzzle input: a file, a `.gz` compressed file or `-` for stdin.
+        /// Defaults to `inputs/day_XX.txt`
+        #[arg(long, requires = "day")]
+        input: Option<Input>,
+        /// Number of timed runs of each step
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+        runs: u64,
+        /// Print the timings as JSON, in nanoseconds
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn report(day: u8, part: u8, result: &str, verdict: Option<&Verdict>) {
+    let verdict = verdict.map(|v| format!(" [{}]", v)).unwrap_or_default();
+    if result.contains('\n') {
+        println!("Day {} part {} result{}:\n{}", day, part, verdict, result);
+    } else {
+        println!("Day {} part {} result: {}{}", day, part, result, verdict);
+    }
+}
+
+/// Solve the requested parts of `day`, returning every part with its result
+fn solve_day(day: u8, part: Option<u8>, input: &Input) -> error::Result<Vec<(u8, String)>> {
+    let solution = solution::solution(day).expect("Day validated by the argument parser");
+    let parsed = input.parse_with(|reader| solution.run_parse(reader))?;
+    let mut results = Vec::new();
+    if part.is_none_or(|p| p == 1) {
+        results.push((1, solution.run_part_one(parsed.as_ref())));
+    }
+    if part.is_none_or(|p| p == 2) {
+        results.push((2, solution.run_part_two(parsed.as_ref())));
+    }
+    Ok(results)
+}
+
+fn run(cli: Cli) -> error::Result<ExitCode> {
+    match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            record,
+            ..
+        } => {
+            let answers_path = Path::new(ANSWERS_FILE);
+            let mut answers = Answers::load(answers_path)?;
+            let runs: Vec<(u8, Option<u8>, Option<Input>)> = match day {
+                Some(day) => vec![(day, part, input)],
+                None => solution::solutions()
+                    .map(|(day, _)| (day, None, None))
+                    .collect(),
+            };
+
+            let (mut failed, mut recorded) = (0usize, 0usize);
+            for (day, part, input) in runs {
+                // Known answers only apply to the puzzle inputs
+                let verify = input.is_none();
+                let input = input.unwrap_or_else(|| Input::day(day));
+                for (part, result) in solve_day(day, part, &input)? {
+                    let verdict = verify.then(|| answers.check(day, part, &result));
+                    match verdict {
+                        Some(Verdict::Fail { .. }) => failed += 1,
+                        Some(Verdict::Unknown) if record => {
+                            answers.record(day, part, &result);
+                            recorded += 1;
+                        }
+                        _ => {}
+                    }
+                    report(day, part, &result, verdict.as_ref());
+                }
+            }
+
+            if recorded > 0 {
+                answers.save(answers_path)?;
+                println!("Recorded {} new answers in {}", recorded, ANSWERS_FILE);
+            }
+            if failed > 0 {
+                eprintln!("{} answers don't match {}", failed, ANSWERS_FILE);
+                return Ok(ExitCode::FAILURE);
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Bench {
+            day,
+            input,
+            runs,
+            json,
+        } => {
+            let mut benches = Vec::new();
+            for (bench_day, solution) in solution::solutions() {
+                if day.is_some_and(|day| day != bench_day) {
+                    continue;
+                }
+                let input = input.clone().unwrap_or_else(|| Input::day(bench_day));
+                let bench = bench::bench_day(bench_day, solution, &input, runs as usize)?;
+                if !json {
+                    print!("{}", bench);
+                }
+                benches.push(bench);
+            }
+            if json {
+                let json =
+                    serde_json::to_string_pretty(&benches).expect("Timings serialize to JSON");
+                println!("{}", json);
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,107 @@
+use crate::error::Result;
+use crate::{
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16, day_17,
+};
+use std::any::Any;
+use std::fmt::Display;
+use std::io::BufRead;
+
+/// A day puzzle: how to read its input and how to answer both of its parts
+pub trait Solution {
+    type Input;
+    type PartOne: Display;
+    type PartTwo: Display;
+
+    fn parse(&self, reader: impl BufRead) -> Result<Self::Input>;
+    fn part_one(&self, input: &Self::Input) -> Self::PartOne;
+    fn part_two(&self, input: &Self::Input) -> Self::PartTwo;
+}
+
+/// Type erased [`Solution`] so every day can live in the same registry
+pub trait DynSolution: Sync {
+    fn run_parse(&self, reader: &mut dyn BufRead) -> Result<Box<dyn Any>>;
+    fn run_part_one(&self, input: &dyn Any) -> String;
+    fn run_part_two(&self, input: &dyn Any) -> String;
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync,
+    S::Input: 'static,
+{
+    fn run_parse(&self, reader: &mut dyn BufRead) -> Result<Box<dyn Any>> {
+        Ok(Box::new(self.parse(reader)?))
+    }
+
+    fn run_part_one(&self, input: &dyn Any) -> String {
+        self.part_one(
+            input
+                .downcast_ref()
+                .expect("Input parsed by the same solution"),
+        )
+        .to_string()
+    }
+
+    fn run_part_two(&self, input: &dyn Any) -> String {
+        self.part_two(
+            input
+                .downcast_ref()
+                .expect("Input parsed by the same solution"),
+        )
+        .to_string()
+    }
+}
+
+pub const DAYS: u8 = 17;
+
+static REGISTRY: [&dyn DynSolution; DAYS as usize] = [
+    &day_01::Day01,
+    &day_02::Day02,
+    &day_03::Day03,
+    &day_04::Day04,
+    &day_05::Day05,
+    &day_06::Day06,
+    &day_07::Day07,
+    &day_08::Day08,
+    &day_09::Day09,
+    &day_10::Day10,
+    &day_11::Day11,
+    &day_12::Day12,
+    &day_13::Day13,
+    &day_14::Day14,
+    &day_15::Day15,
+    &day_16::Day16,
+    &day_17::Day17,
+];
+
+pub fn solution(day: u8) -> Option<&'static dyn DynSolution> {
+    REGISTRY.get(usize::from(day).checked_sub(1)?).copied()
+}
+
+pub fn solutions() -> impl Iterator<Item = (u8, &'static dyn DynSolution)> {
+    (1..=DAYS).zip(REGISTRY.iter().copied())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Result;
+    use crate::solution::{solution, solutions, DAYS};
+
+    #[test]
+    fn registry_covers_every_day() {
+        assert_eq!(solutions().count(), DAYS as usize);
+        assert!(solution(0).is_none());
+        assert!(solution(DAYS + 1).is_none());
+    }
+
+    #[test]
+    fn run_from_registry() -> Result<()> {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+        let day_01 = solution(1).unwrap();
+        let parsed = day_01.run_parse(&mut input.as_bytes())?;
+        assert_eq!(day_01.run_part_one(parsed.as_ref()), "7");
+        assert_eq!(day_01.run_part_two(parsed.as_ref()), "5");
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+/// Reads big endian bit fields out of packed bytes.
+/// Sub-readers borrow the same bytes, so nesting doesn't copy anything.
+#[derive(Debug, Clone)]
+pub struct BitReader<'bytes> {
+    bytes: &'bytes [u8],
+    position: usize,
+    end: usize,
+}
+
+impl<'bytes> BitReader<'bytes> {
+    /// Reader over the first `len` bits of `bytes`
+    pub fn new(bytes: &'bytes [u8], len: usize) -> Self {
+        assert!(
+            len <= bytes.len() * 8,
+            "{} bits out of {} bytes",
+            len,
+            bytes.len()
+        );
+        Self {
+            bytes,
+            position: 0,
+            end: len,
+        }
+    }
+
+    /// Offset of the next bit, from the start of the bytes even for sub-readers
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.end - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Read `n` bits as an unsigned integer, `None` if `n` is above 64 or not enough bits are left.
+    /// Nothing is consumed when the read fails.
+    pub fn read(&mut self, n: usize) -> Option<u64> {
+        if n > 64 || n > self.remaining() {
+            return None;
+        }
+        let mut value = 0u64;
+        let mut left = n;
+        while left > 0 {
+            let byte = self.bytes[self.position / 8];
+            let available = 8 - self.position % 8;
+            let take = available.min(left);
+            let bits = (byte >> (available - take)) & (u8::MAX >> (8 - take));
+            // Two shifts as `take` can be 8 and `value` hold 64 bits already
+            value = (value << (take - 1) << 1) | u64::from(bits);
+            self.position += take;
+            left -= take;
+        }
+        Some(value)
+    }
+
+    pub fn read_bit(&mut self) -> Option<bool> {
+        self.read(1).map(|bit| bit == 1)
+    }
+
+    /// Split off the next `len` bits as their own reader and skip them in this one
+    pub fn sub_reader(&mut self, len: usize) -> Option<BitReader<'bytes>> {
+        if len > self.remaining() {
+            return None;
+        }
+        let sub = Self {
+            bytes: self.bytes,
+            position: self.position,
+            end: self.position + len,
+        };
+        self.position += len;
+        Some(sub)
+    }
+
+    pub fn skip(&mut self, n: usize) -> Option<()> {
+        if n > self.remaining() {
+            return None;
+        }
+        self.position += n;
+        Some(())
+    }
+}
+
+/// Appends big endian bit fields, the counterpart of [`BitReader`]
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().expect("A byte was pushed") |= 0x80 >> (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    /// Write the `n` low bits of `value`, most significant first
+    pub fn write(&mut self, value: u64, n: usize) {
+        assert!(n <= 64, "Can't write {} bits at once", n);
+        for i in (0..n).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn append(&mut self, other: &BitWriter) {
+        let mut reader = other.reader();
+        while let Some(bit) = reader.read_bit() {
+            self.write_bit(bit);
+        }
+    }
+
+    pub fn reader(&self) -> BitReader<'_> {
+        BitReader::new(&self.bytes, self.len)
+    }
+
+    /// The written bits, padded with zeros up to a whole byte
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::bits::{BitReader, BitWriter};
+
+    #[test]
+    fn read_fields() {
+        // 110100101111111000101000
+        let bytes = [0xD2, 0xFE, 0x28];
+        let mut reader = BitReader::new(&bytes, 24);
+        assert_eq!(reader.read(3), Some(6));
+        assert_eq!(reader.read(3), Some(4));
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.position(), 7);
+        assert_eq!(reader.read(9), Some(0b011111110));
+        assert_eq!(reader.remaining(), 8);
+        assert_eq!(reader.read(9), None);
+        assert_eq!(reader.read(8), Some(0x28));
+        assert!(reader.is_empty());
+        assert_eq!(reader.read(0), Some(0));
+    }
+
+    #[test]
+    fn read_wide_fields() {
+        let bytes = [0xFF; 9];
+        let mut reader = BitReader::new(&bytes, 72);
+        assert_eq!(reader.read(4), Some(0xF));
+        assert_eq!(reader.read(64), Some(u64::MAX));
+        assert_eq!(reader.read(65), None);
+    }
+
+    #[test]
+    fn bounded_sub_reader() {
+        let bytes = [0b1010_1100, 0b0101_0000];
+        let mut reader = BitReader::new(&bytes, 12);
+        reader.skip(2).unwrap();
+        let mut sub = reader.sub_reader(6).unwrap();
+        assert_eq!(reader.position(), 8);
+        assert_eq!(sub.position(), 2);
+        assert_eq!(sub.read(6), Some(0b101100));
+        assert_eq!(sub.read(1), None);
+        assert_eq!(reader.read(4), Some(0b0101));
+        assert!(reader.sub_reader(1).is_none());
+    }
+
+    #[test]
+    fn write_then_read() {
+        let mut writer = BitWriter::new();
+        writer.write(6, 3);
+        writer.write_bit(true);
+        writer.write(u64::MAX, 64);
+        let mut tail = BitWriter::new();
+        tail.write(0b10, 2);
+        writer.append(&tail);
+        assert_eq!(writer.len(), 70);
+
+        let mut reader = writer.reader();
+        assert_eq!(reader.read(3), Some(6));
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read(64), Some(u64::MAX));
+        assert_eq!(reader.read(2), Some(0b10));
+        assert!(reader.is_empty());
+        assert_eq!(writer.into_bytes().len(), 9);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::error::ParseError;
+use crate::utils::parse;
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// A cell position as `(row, column)`
+pub type Point = (usize, usize);
+
+static ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+static SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Rectangular grid of cells stored row by row
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (row, column)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Build a grid from rows that all have the same, non zero, length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, ParseError> {
+        let width = rows.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(ParseError::new("rows of cells", "no cells"));
+        }
+        if let Some((row, cells)) = rows.iter().enumerate().find(|(_, r)| r.len() != width) {
+            return Err(ParseError::new(
+                format!("{} cells", width),
+                format!("{} cells", cells.len()),
+            )
+            .at_line(row + 1));
+        }
+        Ok(Self {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parse one character per cell, `cell` names what `parse` accepts for error messages
+    pub fn parse_cells(
+        s: &str,
+        cell: &str,
+        parse: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        Self::from_rows(parse::cell_rows(s, cell, parse)?)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The bottom right cell, `None` for an empty grid
+    pub fn last_point(&self) -> Option<Point> {
+        (!self.cells.is_empty()).then(|| (self.height - 1, self.width - 1))
+    }
+
+    pub fn contains(&self, (row, column): Point) -> bool {
+        row < self.height && column < self.width
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point)
+            .then(|| &self.cells[point.0 * self.width + point.1])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        if self.contains(point) {
+            Some(&mut self.cells[point.0 * self.width + point.1])
+        } else {
+            None
+        }
+    }
+
+    /// `point` moved by `(rows, columns)`, if it stays in the grid
+    pub fn offset(&self, (row, column): Point, (rows, columns): (isize, isize)) -> Option<Point> {
+        let point = (
+            row.checked_add_signed(rows)?,
+            column.checked_add_signed(columns)?,
+        );
+        self.contains(point).then_some(point)
+    }
+
+    /// Up, left, right and down neighbours of `point` in the grid.
+    /// The iterator doesn't borrow the grid so cells can be updated while walking it.
+    pub fn neighbours_4(&self, point: Point) -> impl Iterator<Item = Point> {
+        Self::neighbours(self.width, self.height, point, &ORTHOGONAL)
+    }
+
+    /// Orthogonal and diagonal neighbours of `point` in the grid, see [`Grid::neighbours_4`]
+    pub fn neighbours_8(&self, point: Point) -> impl Iterator<Item = Point> {
+        Self::neighbours(self.width, self.height, point, &SURROUNDING)
+    }
+
+    fn neighbours(
+        width: usize,
+        height: usize,
+        (row, column): Point,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Point> {
+        offsets.iter().filter_map(move |&(rows, columns)| {
+            let point = (
+                row.checked_add_signed(rows)?,
+                column.checked_add_signed(columns)?,
+            );
+            (point.0 < height && point.1 < width).then_some(point)
+        })
+    }
+
+    /// Every point, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// Every cell with its point, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `max(1)` keeps `chunks` happy with empty grids, which have no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        self.cells
+            .iter()
+            .skip(column)
+            .step_by(self.width.max(1))
+            .take(if column < self.width { self.height } else { 0 })
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |column| self.column(column))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Rows become columns
+    pub fn transpose(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(row, column)| {
+            self[(column, row)].clone()
+        })
+    }
+
+    /// Mirror left to right
+    pub fn flip_horizontal(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(row, column)| {
+            self[(row, self.width - 1 - column)].clone()
+        })
+    }
+
+    /// Mirror top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(row, column)| {
+            self[(self.height - 1 - row, column)].clone()
+        })
+    }
+
+    /// Quarter turn clockwise
+    pub fn rotate_clockwise(&self) -> Self {
+        self.transpose().flip_horizontal()
+    }
+
+    /// Quarter turn counter clockwise
+    pub fn rotate_counter_clockwise(&self) -> Self {
+        self.transpose().flip_vertical()
+    }
+}
+
+impl Grid<u8> {
+    /// Parse rows of single digits such as `2199943210`
+    pub fn parse_digits(s: &str) -> Result<Self, ParseError> {
+        Self::from_rows(parse::digit_rows(s)?)
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(s: &str) -> Result<Self, ParseError> {
+        Self::parse_cells(s, "character", Some)
+    }
+}
+
+impl FromStr for Grid<u8> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_digits(s)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} out of a {}x{} grid", point, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} out of a {}x{} grid", point, width, height))
+    }
+}
+
+/// One line per row, cells side by side
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::utils::grid::Grid;
+
+    fn sample() -> Grid<u8> {
+        "123\n456".parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.to_string(), "123\n456\n");
+
+        let chars = Grid::parse_chars("#.\n.#").unwrap();
+        assert_eq!(chars[(1, 1)], '#');
+        assert_eq!(
+            Grid::parse_cells("#.\n.x", "map tile", |c| (c == '#' || c == '.')
+                .then_some(c))
+            .unwrap_err(),
+            ParseError::new("a map tile", "x").at_line(2).at_column(2)
+        );
+        assert!(Grid::from_rows(vec![vec![1], vec![2, 3]]).is_err());
+    }
+
+    #[test]
+    fn reject_ragged_and_empty_rows() {
+        assert_eq!(
+            Grid::parse_digits("123\n45").unwrap_err(),
+            ParseError::new("3 cells", "2 cells").at_line(2)
+        );
+        assert!(Grid::parse_digits("").is_err());
+        assert_eq!(
+            Grid::<u8>::from_rows(vec![vec![], vec![]]).unwrap_err(),
+            ParseError::new("rows of cells", "no cells")
+        );
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let grid = sample();
+        let mut corner: Vec<_> = grid.neighbours_4((0, 0)).collect();
+        corner.sort_unstable();
+        assert_eq!(corner, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours_8((0, 1)).count(), 5);
+        assert_eq!(grid.neighbours_8((1, 1)).count(), 5);
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.offset((0, 0), (1, 2)), Some((1, 2)));
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = sample();
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows, vec![&[1, 2, 3][..], &[4, 5, 6][..]]);
+        let columns: Vec<Vec<u8>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &5)));
+    }
+
+    #[test]
+    fn transforms() {
+        let grid = sample();
+        assert_eq!(grid.transpose().to_string(), "14\n25\n36\n");
+        assert_eq!(grid.flip_horizontal().to_string(), "321\n654\n");
+        assert_eq!(grid.flip_vertical().to_string(), "456\n123\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "41\n52\n63\n");
+        assert_eq!(grid.rotate_counter_clockwise().to_string(), "36\n25\n14\n");
+        assert_eq!(grid.map(|n| n * 2).to_string(), "246\n81012\n");
+        let mut grid = grid;
+        grid[(0, 0)] = 9;
+        assert_eq!(grid.values().copied().max(), Some(9));
+    }
+}
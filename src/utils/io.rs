@@ -1,38 +1,278 @@
+use crate::error::{Error, Result};
+use flate2::read::GzDecoder;
+use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter};
 use std::fs;
-use std::io;
-use std::io::{BufRead, BufReader, Read};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-pub fn open_file_read(path: &Path) -> io::Result<impl BufRead> {
-    Ok(BufReader::new(fs::File::open(path)?))
+/// Where a puzzle input is read from
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Input {
+    File(PathBuf),
+    /// Gzip compressed file
+    Gzip(PathBuf),
+    Stdin,
+    Inline(String),
 }
 
-pub fn read_vec_from_file<T: FromStr>(path: &Path) -> io::Result<Vec<T>> {
-    let reader = open_file_read(path)?;
+impl Input {
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::File(path.into())
+    }
+
+    pub fn inline(content: impl Into<String>) -> Self {
+        Self::Inline(content.into())
+    }
+
+    /// The puzzle input of `day`, `inputs/day_XX.txt`
+    pub fn day(day: u8) -> Self {
+        Self::file(format!("./inputs/day_{:02}.txt", day))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Input::File(path) | Input::Gzip(path) => Some(path),
+            Input::Stdin | Input::Inline(_) => None,
+        }
+    }
+
+    pub fn open(&self) -> Result<Box<dyn BufRead + '_>> {
+        Ok(match self {
+            Input::File(path) => Box::new(open_file_read(path)?),
+            Input::Gzip(path) => {
+                let file = fs::File::open(path).map_err(|e| Error::from(e).with_path(path))?;
+                Box::new(BufReader::new(GzDecoder::new(file)))
+            }
+            Input::Stdin => Box::new(std::io::stdin().lock()),
+            Input::Inline(content) => Box::new(Cursor::new(content.as_bytes())),
+        })
+    }
+
+    /// Open the input and hand it to `parser`, errors are tagged with the input path if any
+    pub fn parse_with<T>(&self, parser: impl FnOnce(&mut dyn BufRead) -> Result<T>) -> Result<T> {
+        let result = self.open().and_then(|mut reader| parser(&mut reader));
+        match self.path() {
+            Some(path) => result.map_err(|e| e.with_path(path)),
+            None => result,
+        }
+    }
+}
+
+/// `-` is stdin, paths ending in `.gz` are gzip compressed files, anything else a plain file
+impl FromStr for Input {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "-" => Self::Stdin,
+            path if path.ends_with(".gz") => Self::Gzip(path.into()),
+            path => Self::File(path.into()),
+        })
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::File(path) | Input::Gzip(path) => write!(f, "{}", path.display()),
+            Input::Stdin => write!(f, "<stdin>"),
+            Input::Inline(_) => write!(f, "<inline>"),
+        }
+    }
+}
+
+pub fn open_file_read(path: &Path) -> Result<impl BufRead> {
+    Ok(BufReader::new(
+        fs::File::open(path).map_err(|e| Error::from(e).with_path(path))?,
+    ))
+}
+
+pub fn read_vec<T>(reader: impl BufRead) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: StdError + Send + Sync + 'static,
+{
     let mut res = Vec::new();
 
-    for line in reader.lines() {
+    for (i, line) in reader.lines().enumerate() {
         let line = line?;
-        res.push(line.trim().parse().map_err(|_e| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Invalid data: {}", line),
-            )
-        })?);
+        res.push(
+            line.trim()
+                .parse()
+                .map_err(|e| Error::parse_line(i + 1, &line, e))?,
+        );
     }
 
     Ok(res)
 }
 
-pub fn read_object_from_file<T: FromStr>(path: &Path) -> io::Result<T> {
-    let mut reader = open_file_read(path)?;
+pub fn read_vec_from_file<T>(path: &Path) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: StdError + Send + Sync + 'static,
+{
+    read_vec(open_file_read(path)?).map_err(|e| e.with_path(path))
+}
+
+pub fn read_object<T>(mut reader: impl BufRead) -> Result<T>
+where
+    T: FromStr,
+    T::Err: StdError + Send + Sync + 'static,
+{
+    let mut buff = String::new();
+    reader.read_to_string(&mut buff)?;
+    buff.parse().map_err(Error::parse)
+}
+
+pub fn read_object_from_file<T>(path: &Path) -> Result<T>
+where
+    T: FromStr,
+    T::Err: StdError + Send + Sync + 'static,
+{
+    read_object(open_file_read(path)?).map_err(|e| e.with_path(path))
+}
+
+/// Number of line breaks in the whitespace `s` starts with
+fn leading_lines(s: &str) -> usize {
+    s[..s.len() - s.trim_start().len()].matches('\n').count()
+}
+
+/// Values split by `separator`, errors are located on the line the value is on
+pub fn read_separated_vec<T>(mut reader: impl BufRead, separator: char) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: StdError + Send + Sync + 'static,
+{
     let mut buff = String::new();
     reader.read_to_string(&mut buff)?;
-    buff.parse().map_err(|_e| {
-        std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Couldn't parse: \n{}", buff),
-        )
-    })
+    let mut line = 1 + leading_lines(&buff);
+    let mut res = Vec::new();
+
+    for item in buff.trim().split(separator) {
+        let value = item.trim();
+        let value_line = line + leading_lines(item);
+        res.push(
+            value
+                .parse()
+                .map_err(|e| Error::parse_line(value_line, value, e))?,
+        );
+        line += item.matches('\n').count() + usize::from(separator == '\n');
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::utils::io::{read_object, read_separated_vec, read_vec, read_vec_from_file, Input};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+
+    #[test]
+    fn input_from_argument() {
+        assert_eq!("-".parse::<Input>().unwrap(), Input::Stdin);
+        assert_eq!(
+            "./inputs/day_01.txt".parse::<Input>().unwrap(),
+            Input::file("./inputs/day_01.txt")
+        );
+        assert_eq!(
+            "./inputs/day_01.txt.gz".parse::<Input>().unwrap(),
+            Input::Gzip("./inputs/day_01.txt.gz".into())
+        );
+    }
+
+    #[test]
+    fn read_inline_input() -> crate::error::Result<()> {
+        let input = Input::inline("1\n2\n3");
+        let data: Vec<usize> = input.parse_with(|reader| read_vec(reader))?;
+        assert_eq!(data, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn read_gzip_input() -> crate::error::Result<()> {
+        // Unique to the process, runs of the tests must not share the file
+        let path = std::env::temp_dir().join(format!(
+            "advent_code_2021_{}_read_gzip_input.txt.gz",
+            std::process::id()
+        ));
+        let mut encoder = GzEncoder::new(std::fs::File::create(&path)?, Compression::default());
+        encoder.write_all(b"199\n200\n208")?;
+        encoder.finish()?;
+
+        let input: Input = path.to_str().unwrap().parse().unwrap();
+        let data: Vec<usize> = input.parse_with(|reader| read_vec(reader))?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(data, vec![199, 200, 208]);
+        Ok(())
+    }
+
+    #[test]
+    fn input_errors_keep_path() {
+        let input = Input::file("./inputs/day_01.txt");
+        let error = input
+            .parse_with(|reader| read_object::<usize>(reader))
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::Parse { path: Some(p), .. } if p == Path::new("./inputs/day_01.txt"))
+        );
+    }
+
+    #[test]
+    fn bad_line_is_located() {
+        let input = "199\n200\n2o8\n210";
+        let error = read_vec::<usize>(input.as_bytes()).unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::Parse { line: Some(3), text: Some(text), .. } if text == "2o8"
+        ));
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn bad_separated_value_is_located() {
+        let error = read_separated_vec::<usize>("3,4,x,1".as_bytes(), ',').unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Parse { line: Some(1), text: Some(text), .. } if text == "x"
+        ));
+        let error = read_separated_vec::<usize>("\n3,4,\n5,\n x,1".as_bytes(), ',').unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Parse { line: Some(4), text: Some(text), .. } if text == "x"
+        ));
+        let error = read_separated_vec::<usize>("3\n4\n5\nx".as_bytes(), '\n').unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Parse { line: Some(4), text: Some(text), .. } if text == "x"
+        ));
+    }
+
+    #[test]
+    fn object_error_does_not_dump_input() {
+        let error = read_object::<usize>("12\n34".as_bytes()).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Parse {
+                line: None,
+                text: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn missing_file_keeps_path() {
+        let path = PathBuf::from_str("./inputs/day_00.txt").unwrap();
+        let error = read_vec_from_file::<usize>(&path).unwrap_err();
+        assert!(matches!(error, Error::Io { path: Some(p), .. } if p == path));
+    }
 }
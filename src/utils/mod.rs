@@ -1 +1,5 @@
+pub mod bits;
+pub mod grid;
 pub mod io;
+pub mod num;
+pub mod parse;
@@ -0,0 +1,106 @@
+use num_bigint::BigUint;
+use std::fmt::Display;
+
+/// Unsigned numbers a computation can run on.
+/// Machine integers report overflows as `None`, [`BigUint`] never overflows.
+pub trait Natural: Clone + Ord + Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// `None` if `n` doesn't fit
+    fn from_usize(n: usize) -> Option<Self>;
+    /// `None` if `n` doesn't fit
+    fn from_big(n: &BigUint) -> Option<Self>;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! natural_int {
+    ($($int:ty),*) => {
+        $(
+            impl Natural for $int {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn from_usize(n: usize) -> Option<Self> {
+                    <$int>::try_from(n).ok()
+                }
+
+                fn from_big(n: &BigUint) -> Option<Self> {
+                    <$int>::try_from(n).ok()
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    <$int>::checked_add(*self, *other)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    <$int>::checked_mul(*self, *other)
+                }
+            }
+        )*
+    };
+}
+
+natural_int!(u32, u64, usize, u128);
+
+impl Natural for BigUint {
+    fn zero() -> Self {
+        BigUint::from(0u8)
+    }
+
+    fn one() -> Self {
+        BigUint::from(1u8)
+    }
+
+    fn from_usize(n: usize) -> Option<Self> {
+        Some(BigUint::from(n))
+    }
+
+    fn from_big(n: &BigUint) -> Option<Self> {
+        Some(n.clone())
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::num::Natural;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn checked_words() {
+        assert_eq!(Natural::checked_add(&u32::MAX, &0), Some(u32::MAX));
+        assert_eq!(Natural::checked_add(&u32::MAX, &1), None);
+        assert_eq!(Natural::checked_mul(&(1u64 << 32), &(1u64 << 32)), None);
+        assert_eq!(u64::from_big(&BigUint::from(u64::MAX)), Some(u64::MAX));
+        assert_eq!(u64::from_big(&(BigUint::from(u64::MAX) + 1u8)), None);
+        assert_eq!(u32::from_usize(u32::MAX as usize), Some(u32::MAX));
+        assert_eq!(u32::from_usize(u32::MAX as usize + 1), None);
+    }
+
+    #[test]
+    fn big_numbers_dont_overflow() {
+        let big = BigUint::from(u64::MAX);
+        let square = big.checked_mul(&big).unwrap();
+        assert_eq!(
+            square.to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(
+            BigUint::zero().checked_add(&BigUint::one()),
+            Some(BigUint::one())
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::error::ParseError;
+
+/// Parse rows of single digits such as `2199943210`
+pub fn digit_rows(s: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    cell_rows(s, "digit", |c| c.to_digit(10).map(|d| d as u8))
+}
+
+/// Parse rows of one character cells, rows may differ in width.
+/// `cell` names what `parse` accepts, for error messages.
+pub fn cell_rows<T>(
+    s: &str,
+    cell: &str,
+    mut parse: impl FnMut(char) -> Option<T>,
+) -> Result<Vec<Vec<T>>, ParseError> {
+    s.trim_end()
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            line.trim_end()
+                .chars()
+                .enumerate()
+                .map(|(column, c)| {
+                    parse(c).ok_or_else(|| {
+                        ParseError::new(format!("a {}", cell), c)
+                            .at_line(row + 1)
+                            .at_column(column + 1)
+                    })
+                })
+                .collect::<Result<Vec<T>, _>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::utils::parse::digit_rows;
+
+    #[test]
+    fn parse_digit_rows() {
+        assert_eq!(
+            digit_rows("123\n456\n").unwrap(),
+            vec![vec![1, 2, 3], vec![4, 5, 6]]
+        );
+    }
+
+    #[test]
+    fn locate_bad_digit() {
+        assert_eq!(
+            digit_rows("123\n4x6").unwrap_err(),
+            ParseError::new("a digit", "x").at_line(2).at_column(2)
+        );
+    }
+}